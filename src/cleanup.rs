@@ -0,0 +1,115 @@
+use crate::State;
+use lol_html::html_content::{ContentType, Element};
+use std::borrow::Cow;
+
+/// Collapse runs of whitespace into a single space
+#[inline]
+fn collapse_whitespace(text: &str) -> Cow<'_, str> {
+    let mut previous_whitespace = false;
+    let collapsed = text.bytes().all(|byte| {
+        let whitespace = byte.is_ascii_whitespace();
+        let collapsed = !whitespace || (byte == b' ' && !previous_whitespace);
+        previous_whitespace = whitespace;
+        collapsed
+    });
+
+    if collapsed {
+        return Cow::Borrowed(text);
+    }
+
+    let mut acc = String::with_capacity(text.len());
+    for chr in text.chars() {
+        if !chr.is_ascii_whitespace() {
+            acc.push(chr);
+        } else if !acc.ends_with(' ') {
+            acc.push(' ');
+        }
+    }
+
+    Cow::Owned(acc)
+}
+
+impl State {
+    /// Collapse runs of whitespace into a single space, including the whitespace at the end of the text we emitted before
+    pub(crate) fn collapse_whitespace<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        let mut collapsed = collapse_whitespace(text);
+        if self.trailing_whitespace.get() && collapsed.starts_with(' ') {
+            collapsed = match collapsed {
+                Cow::Borrowed(collapsed) => Cow::Borrowed(&collapsed[1..]),
+                Cow::Owned(mut collapsed) => {
+                    collapsed.remove(0);
+                    Cow::Owned(collapsed)
+                }
+            };
+        }
+
+        if !collapsed.is_empty() {
+            self.trailing_whitespace.set(collapsed.ends_with(' '));
+        }
+
+        collapsed
+    }
+
+    /// Emit the start tag of the element we just entered
+    ///
+    /// If deferred, the start tag is held back until the element gets content, so the element can be removed if it ends up empty
+    pub(crate) fn emit_start_tag(
+        &self,
+        element: &mut Element<'_, '_>,
+        tag_name: &str,
+        defer: bool,
+    ) {
+        if defer && element.can_have_content() {
+            let depth = self.elements.borrow().len() - 1;
+            let start_tag = crate::start_tag(element, tag_name);
+            self.deferred_start_tags
+                .borrow_mut()
+                .push((depth, start_tag));
+            element.start_tag().replace("", ContentType::Html);
+            return;
+        }
+
+        let start_tags = self.emit_content(tag_name == "br");
+        if !start_tags.is_empty() {
+            element.before(&start_tags, ContentType::Html);
+        }
+
+        self.trailing_whitespace.set(false);
+    }
+
+    /// Note that we emitted content, returning the start tags we held back for the elements it is inside of
+    pub(crate) fn emit_content(&self, line_break: bool) -> String {
+        if line_break {
+            self.line_breaks.set(self.line_breaks.get() + 1);
+        } else {
+            self.line_breaks.set(0);
+        }
+
+        let start_tags: String = self
+            .deferred_start_tags
+            .take()
+            .into_iter()
+            .map(|(_depth, start_tag)| start_tag)
+            .collect();
+
+        if !start_tags.is_empty() {
+            self.trailing_whitespace.set(false);
+        }
+
+        start_tags
+    }
+
+    /// Forget the start tag we held back for the element at the depth, returning whether there was one (meaning the element is empty)
+    pub(crate) fn discard_start_tag(&self, depth: usize) -> bool {
+        let mut deferred_start_tags = self.deferred_start_tags.borrow_mut();
+        if deferred_start_tags
+            .last()
+            .is_some_and(|(deferred_depth, _start_tag)| *deferred_depth == depth)
+        {
+            deferred_start_tags.pop();
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::BubbleBath;
+use ahash::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Keys mapped to sets of names (`allowed_foreign_tags`, `allowed_tag_attributes` and `clean_url_attributes`)
+///
+/// `allowed_foreign_tag_attributes` maps namespaces to these
+type Sets<'a, K> = HashMap<K, HashSet<&'a str>>;
+
+/// Tags mapped to attributes and their values (`set_tag_attributes` and friends)
+type TagAttributeValues<'a> = HashMap<&'a str, HashMap<&'a str, &'a str>>;
+
+#[inline]
+fn union_sets<'a, K>(left: &mut Sets<'a, K>, right: &Sets<'a, K>)
+where
+    K: Copy + Eq + Hash,
+{
+    for (key, names) in right {
+        left.entry(*key).or_default().extend(names.iter().copied());
+    }
+}
+
+#[inline]
+fn intersect_sets<'a, K>(left: &mut Sets<'a, K>, right: &Sets<'a, K>)
+where
+    K: Copy + Eq + Hash,
+{
+    left.retain(|key, names| {
+        let Some(other_names) = right.get(key) else {
+            return false;
+        };
+
+        names.retain(|name| other_names.contains(name));
+        !names.is_empty()
+    });
+}
+
+#[inline]
+fn subtract_sets<'a, K>(left: &mut Sets<'a, K>, right: &Sets<'a, K>)
+where
+    K: Copy + Eq + Hash,
+{
+    left.retain(|key, names| {
+        if let Some(other_names) = right.get(key) {
+            names.retain(|name| !other_names.contains(name));
+        }
+
+        !names.is_empty()
+    });
+}
+
+#[inline]
+fn union_namespaced_sets<'a, N>(
+    left: &mut HashMap<N, Sets<'a, &'a str>>,
+    right: &HashMap<N, Sets<'a, &'a str>>,
+) where
+    N: Copy + Eq + Hash,
+{
+    for (namespace, sets) in right {
+        union_sets(left.entry(*namespace).or_default(), sets);
+    }
+}
+
+#[inline]
+fn intersect_namespaced_sets<'a, N>(
+    left: &mut HashMap<N, Sets<'a, &'a str>>,
+    right: &HashMap<N, Sets<'a, &'a str>>,
+) where
+    N: Copy + Eq + Hash,
+{
+    left.retain(|namespace, sets| {
+        let Some(other_sets) = right.get(namespace) else {
+            return false;
+        };
+
+        intersect_sets(sets, other_sets);
+        !sets.is_empty()
+    });
+}
+
+#[inline]
+fn subtract_namespaced_sets<'a, N>(
+    left: &mut HashMap<N, Sets<'a, &'a str>>,
+    right: &HashMap<N, Sets<'a, &'a str>>,
+) where
+    N: Copy + Eq + Hash,
+{
+    left.retain(|namespace, sets| {
+        if let Some(other_sets) = right.get(namespace) {
+            subtract_sets(sets, other_sets);
+        }
+
+        !sets.is_empty()
+    });
+}
+
+#[inline]
+fn union_values<'a>(left: &mut TagAttributeValues<'a>, right: &TagAttributeValues<'a>) {
+    for (tag_name, attributes) in right {
+        left.entry(tag_name)
+            .or_default()
+            .extend(attributes.iter().map(|(name, value)| (*name, *value)));
+    }
+}
+
+#[inline]
+fn intersect_values<'a>(left: &mut TagAttributeValues<'a>, right: &TagAttributeValues<'a>) {
+    left.retain(|tag_name, attributes| {
+        let Some(other_attributes) = right.get(tag_name) else {
+            return false;
+        };
+
+        attributes.retain(|name, value| {
+            let Some(other_value) = other_attributes.get(name) else {
+                return false;
+            };
+
+            *value = other_value;
+            true
+        });
+        !attributes.is_empty()
+    });
+}
+
+#[inline]
+fn subtract_values<'a>(left: &mut TagAttributeValues<'a>, right: &TagAttributeValues<'a>) {
+    left.retain(|tag_name, attributes| {
+        if let Some(other_attributes) = right.get(tag_name) {
+            attributes.retain(|name, _value| !other_attributes.contains_key(name));
+        }
+
+        !attributes.is_empty()
+    });
+}
+
+impl BubbleBath<'static> {
+    /// Policy that doesn't allow or remove anything
+    ///
+    /// All tags are removed while keeping their content. Meant as a base for composing policies,
+    /// for example to [subtract](Self::subtract) a single tag from another policy
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            allowed_url_schemes: HashSet::default(),
+            remove_content_tags: HashSet::default(),
+            ..Self::text_only()
+        }
+    }
+}
+
+impl<'a> BubbleBath<'a> {
+    /// Remove the tags whose content is removed from the allowed tags
+    #[inline]
+    fn resolve_conflicts(&mut self) {
+        let remove_content_tags = &self.remove_content_tags;
+        self.allowed_tags
+            .retain(|tag_name| !remove_content_tags.contains(tag_name));
+    }
+
+    /// Allow everything either of the policies allows
+    ///
+    /// ## Conflicts
+    ///
+    /// The operations distinguish between permissions (tags, attributes and URL schemes) and restrictions (`remove_content_tags` and `clean_url_attributes`).
+    /// Permissions follow the operation, restrictions are never loosened: they are merged by [`Self::union`] and [`Self::intersect`] and kept by [`Self::subtract`].
+    ///
+    /// - Tags in `remove_content_tags` are removed from `allowed_tags`, so removing the content wins over allowing the tag
+    /// - If both policies set the same attribute to different values, the value of the other policy wins
+    /// - All other options (hooks, sanitizers, etc.) are kept from this policy
+    #[must_use]
+    pub fn union(mut self, other: &BubbleBath<'a>) -> Self {
+        self.allowed_tags.extend(other.allowed_tags.iter().copied());
+        union_sets(&mut self.allowed_foreign_tags, &other.allowed_foreign_tags);
+        self.allowed_generic_attributes
+            .extend(other.allowed_generic_attributes.iter().copied());
+        union_sets(
+            &mut self.allowed_tag_attributes,
+            &other.allowed_tag_attributes,
+        );
+        union_namespaced_sets(
+            &mut self.allowed_foreign_tag_attributes,
+            &other.allowed_foreign_tag_attributes,
+        );
+        self.allowed_url_schemes
+            .extend(other.allowed_url_schemes.iter().copied());
+
+        union_sets(&mut self.clean_url_attributes, &other.clean_url_attributes);
+        self.remove_content_tags
+            .extend(other.remove_content_tags.iter().copied());
+
+        union_values(&mut self.set_tag_attributes, &other.set_tag_attributes);
+        union_values(
+            &mut self.default_tag_attributes,
+            &other.default_tag_attributes,
+        );
+        union_values(&mut self.merge_tag_attributes, &other.merge_tag_attributes);
+
+        self.resolve_conflicts();
+        self
+    }
+
+    /// Only allow what both of the policies allow
+    ///
+    /// Only the attributes both policies set are kept. Check [`Self::union`] for how conflicts are resolved
+    #[must_use]
+    pub fn intersect(mut self, other: &BubbleBath<'a>) -> Self {
+        self.allowed_tags
+            .retain(|tag_name| other.allowed_tags.contains(tag_name));
+        intersect_sets(&mut self.allowed_foreign_tags, &other.allowed_foreign_tags);
+        self.allowed_generic_attributes
+            .retain(|attribute| other.allowed_generic_attributes.contains(attribute));
+        intersect_sets(
+            &mut self.allowed_tag_attributes,
+            &other.allowed_tag_attributes,
+        );
+        intersect_namespaced_sets(
+            &mut self.allowed_foreign_tag_attributes,
+            &other.allowed_foreign_tag_attributes,
+        );
+        self.allowed_url_schemes
+            .retain(|scheme| other.allowed_url_schemes.contains(scheme));
+
+        union_sets(&mut self.clean_url_attributes, &other.clean_url_attributes);
+        self.remove_content_tags
+            .extend(other.remove_content_tags.iter().copied());
+
+        intersect_values(&mut self.set_tag_attributes, &other.set_tag_attributes);
+        intersect_values(
+            &mut self.default_tag_attributes,
+            &other.default_tag_attributes,
+        );
+        intersect_values(&mut self.merge_tag_attributes, &other.merge_tag_attributes);
+
+        self.resolve_conflicts();
+        self
+    }
+
+    /// Remove everything the other policy allows from this policy
+    ///
+    /// Attributes the other policy sets are no longer set. Check [`Self::union`] for how conflicts are resolved
+    #[must_use]
+    pub fn subtract(mut self, other: &BubbleBath<'a>) -> Self {
+        self.allowed_tags
+            .retain(|tag_name| !other.allowed_tags.contains(tag_name));
+        subtract_sets(&mut self.allowed_foreign_tags, &other.allowed_foreign_tags);
+        self.allowed_generic_attributes
+            .retain(|attribute| !other.allowed_generic_attributes.contains(attribute));
+        subtract_sets(
+            &mut self.allowed_tag_attributes,
+            &other.allowed_tag_attributes,
+        );
+        subtract_namespaced_sets(
+            &mut self.allowed_foreign_tag_attributes,
+            &other.allowed_foreign_tag_attributes,
+        );
+        self.allowed_url_schemes
+            .retain(|scheme| !other.allowed_url_schemes.contains(scheme));
+
+        subtract_values(&mut self.set_tag_attributes, &other.set_tag_attributes);
+        subtract_values(
+            &mut self.default_tag_attributes,
+            &other.default_tag_attributes,
+        );
+        subtract_values(&mut self.merge_tag_attributes, &other.merge_tag_attributes);
+
+        self.resolve_conflicts();
+        self
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{namespace::Namespace, State};
+use lol_html::html_content::{ContentType, Element};
+
+/// Elements that can't be inside of `p` tags, since browsers close the `p` tag before them
+#[rustfmt::skip]
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "details", "dialog", "div", "dl", "fieldset",
+    "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header",
+    "hgroup", "hr", "main", "menu", "nav", "ol", "p", "pre", "section", "summary", "ul",
+];
+
+/// Lists that `li` tags are valid in
+const LIST_ELEMENTS: &[&str] = &["menu", "ol", "ul"];
+
+/// Elements whose content doesn't belong to the elements they are inside of (for example, a `li` inside of a table cell isn't part of a list around the table)
+const SCOPE_BOUNDARIES: &[&str] = &["button", "caption", "table", "td", "th"];
+
+/// Strategy for elements that violate the content model of the elements they are inside of
+///
+/// Content models are enforced for `li` (only inside of `ul`, `ol` and `menu`), `dt` and `dd` (only inside of `dl`),
+/// `a` (not inside of other `a` tags), block elements like `div` (not inside of `p`), `figcaption` (only inside of `figure`)
+/// and `summary` (only as the first child element of `details`).
+///
+/// Omitted end tags are always closed explicitly, whether the element is closed by a sibling (for example, `li` by the next `li`)
+/// or by the end tag of an element it is inside of
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentModel {
+    /// Close the elements the element isn't allowed in, moving it up to the nearest valid position
+    ///
+    /// This is what browsers do for some of the violations (for example, a `div` closes the `p` it is inside of).
+    /// Elements without a valid position (for example, `li` tags outside of lists) are removed while keeping their content
+    Move,
+
+    /// Remove the elements that violate a content model while keeping their content
+    Unwrap,
+}
+
+/// Position of an element relative to the elements we are currently inside of
+enum Placement {
+    /// The element is valid where it is
+    Valid,
+
+    /// The element closes the elements from the depth onwards, since their end tags were omitted
+    Close(usize),
+
+    /// The element is invalid, but would be valid after closing the elements from the depth onwards
+    Move(usize),
+
+    /// The element is invalid, and can't be moved to a valid position
+    Invalid,
+}
+
+/// Position of the element, given the names and child counts of the elements it is inside of, and the depth its scope starts at
+fn placement(elements: &[String], children: &[usize], scope: usize, tag_name: &str) -> Placement {
+    let in_scope = || elements.iter().enumerate().skip(scope).rev();
+    let is_parent = |depth: usize| depth + 1 == elements.len();
+
+    let placement = match tag_name {
+        "dd" | "dt" | "li" => {
+            let (containers, siblings): (&[&str], &[&str]) = if tag_name == "li" {
+                (LIST_ELEMENTS, &["li"])
+            } else {
+                (&["dl"], &["dd", "dt"])
+            };
+
+            match in_scope().find(|(_, name)| {
+                containers.contains(&name.as_str()) || siblings.contains(&name.as_str())
+            }) {
+                Some((depth, name)) if siblings.contains(&name.as_str()) => Placement::Close(depth),
+                // Groups of `dt` and `dd` tags can be wrapped in a `div`
+                Some((depth, name))
+                    if is_parent(depth) || (name == "dl" && elements[depth + 1..] == ["div"]) =>
+                {
+                    Placement::Valid
+                }
+                Some((depth, _)) => Placement::Move(depth + 1),
+                None => Placement::Invalid,
+            }
+        }
+        "a" => in_scope()
+            .find(|(_, name)| *name == "a")
+            .map_or(Placement::Valid, |(depth, _)| Placement::Move(depth)),
+        "figcaption" => match in_scope().find(|(_, name)| *name == "figure") {
+            Some((depth, _)) if is_parent(depth) => Placement::Valid,
+            Some((depth, _)) => Placement::Move(depth + 1),
+            None => Placement::Invalid,
+        },
+        "summary" => {
+            if elements.last().is_some_and(|name| name == "details") && children.last() == Some(&0)
+            {
+                Placement::Valid
+            } else {
+                Placement::Invalid
+            }
+        }
+        _ => Placement::Valid,
+    };
+
+    if !matches!(placement, Placement::Valid) || !BLOCK_ELEMENTS.contains(&tag_name) {
+        return placement;
+    }
+
+    match in_scope().find(|(_, name)| *name == "p") {
+        Some((depth, _)) if tag_name == "p" => Placement::Close(depth),
+        Some((depth, _)) => Placement::Move(depth),
+        None => Placement::Valid,
+    }
+}
+
+impl State {
+    /// Close the elements whose end tags were omitted, and apply the strategy if the element violates a content model
+    ///
+    /// Returns `false` if the element should be removed while keeping its content
+    pub(crate) fn enforce_content_model(
+        &self,
+        element: &mut Element<'_, '_>,
+        tag_name: &str,
+        content_model: ContentModel,
+    ) -> bool {
+        let mut insert = String::new();
+        let valid = loop {
+            let placement = {
+                let elements = self.elements.borrow();
+                let namespaces = self.namespaces.borrow();
+                let scope = elements
+                    .iter()
+                    .zip(namespaces.iter())
+                    .rposition(|(name, namespace)| {
+                        *namespace != Namespace::Html || SCOPE_BOUNDARIES.contains(&name.as_str())
+                    })
+                    .map_or(0, |depth| depth + 1);
+
+                placement(&elements, &self.children.borrow(), scope, tag_name)
+            };
+
+            match placement {
+                Placement::Valid => break true,
+                Placement::Close(depth) => insert.push_str(&self.close_elements(depth)),
+                Placement::Move(depth) if content_model == ContentModel::Move => {
+                    insert.push_str(&self.close_elements(depth));
+                }
+                Placement::Move(_) | Placement::Invalid => break false,
+            }
+        };
+
+        if !insert.is_empty() {
+            element.before(&insert, ContentType::Html);
+        }
+
+        valid
+    }
+}
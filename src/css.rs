@@ -0,0 +1,578 @@
+use crate::{hashmap, hashset, url_scheme};
+use ahash::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Values a CSS property is allowed to take
+pub enum CssValue<'a> {
+    /// Any value, as long as it only uses allowed functions
+    Any,
+
+    /// Colour values (hex notation, named colours and the colour functions in [`CssSanitizer::allowed_functions`])
+    Color,
+
+    /// Lengths and percentages, up to four of them (as used by shorthands like `margin`)
+    Length,
+
+    /// A space-separated list of the provided keywords
+    Keywords(HashSet<&'a str>),
+}
+
+/// CSS sanitizer
+///
+/// Parses CSS declarations (as found in `style` attributes) and only keeps the ones that pass the allow-list
+///
+/// Declarations containing escape sequences are always discarded since they are commonly used to obfuscate payloads.
+/// The same goes for declarations of properties that can execute code (`behavior` and `-moz-binding`), no matter what the allow-list says
+pub struct CssSanitizer<'a> {
+    /// CSS functions you want to allow in values
+    ///
+    /// Arguments of `url()` are additionally checked against [`BubbleBath::allowed_url_schemes`](crate::BubbleBath::allowed_url_schemes)
+    ///
+    /// **Note**: Think twice before putting `expression` in here. It is really damn evil!
+    pub allowed_functions: HashSet<&'a str>,
+
+    /// Properties you want to keep, mapped to the values they are allowed to take
+    pub allowed_properties: HashMap<&'a str, CssValue<'a>>,
+}
+
+/// Properties that are never allowed, no matter what the allow-list says
+const DENIED_PROPERTIES: &[&str] = &["-moz-binding", "behavior"];
+
+enum Token<'s> {
+    Delimiter,
+    Function { name: &'s str, arguments: &'s str },
+    Hash(&'s str),
+    Ident(&'s str),
+    Number(&'s str),
+    String,
+}
+
+#[inline]
+fn is_ident_char(chr: char) -> bool {
+    chr.is_alphanumeric() || chr == '-' || chr == '_' || !chr.is_ascii()
+}
+
+#[inline]
+fn is_numeric_start(chr: char, next: Option<char>) -> bool {
+    chr.is_ascii_digit()
+        || (matches!(chr, '.' | '+' | '-')
+            && next.is_some_and(|next| next.is_ascii_digit() || next == '.'))
+}
+
+/// Find the end of a quoted string starting at `start`
+///
+/// Returns the byte index right after the closing quote
+#[inline]
+fn string_end(input: &str, start: usize) -> Option<usize> {
+    let quote = input[start..].chars().next()?;
+    let rest = &input[start + 1..];
+    let end = rest.find([quote, '\n'])?;
+
+    (rest[end..].starts_with(quote)).then_some(start + 1 + end + 1)
+}
+
+/// Find the closing delimiter of a block starting right after the opening one
+///
+/// Returns the byte index of the closing delimiter
+#[inline]
+fn block_end(input: &str, start: usize, open: char, close: char) -> Option<usize> {
+    let mut depth = 0_usize;
+    let mut idx = start;
+
+    while let Some(chr) = input[idx..].chars().next() {
+        match chr {
+            '"' | '\'' => {
+                idx = string_end(input, idx)?;
+                continue;
+            }
+            chr if chr == open => depth += 1,
+            chr if chr == close && depth == 0 => return Some(idx),
+            chr if chr == close => depth -= 1,
+            _ => {}
+        }
+
+        idx += chr.len_utf8();
+    }
+
+    None
+}
+
+/// Find the end of a rule prelude, meaning the first `;` or `{` outside of strings and parentheses
+#[inline]
+fn prelude_end(input: &str) -> Option<usize> {
+    let mut idx = 0;
+
+    while let Some(chr) = input[idx..].chars().next() {
+        match chr {
+            '"' | '\'' => {
+                idx = string_end(input, idx)?;
+                continue;
+            }
+            '(' => {
+                idx = block_end(input, idx + 1, '(', ')')? + 1;
+                continue;
+            }
+            ';' | '{' => return Some(idx),
+            _ => {}
+        }
+
+        idx += chr.len_utf8();
+    }
+
+    None
+}
+
+fn tokenize(input: &str) -> Option<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut idx = 0;
+
+    while let Some(chr) = input[idx..].chars().next() {
+        let start = idx;
+        idx += chr.len_utf8();
+        let next = input[idx..].chars().next();
+
+        let token = match chr {
+            chr if chr.is_whitespace() => continue,
+            ',' | '/' => Token::Delimiter,
+            '"' | '\'' => {
+                idx = string_end(input, start)?;
+                Token::String
+            }
+            '#' => {
+                idx = input[idx..]
+                    .find(|chr| !is_ident_char(chr))
+                    .map_or(input.len(), |end| idx + end);
+
+                Token::Hash(&input[start + 1..idx])
+            }
+            chr if is_numeric_start(chr, next) => {
+                idx = input[idx..]
+                    .find(|chr: char| !(chr.is_ascii_digit() || chr == '.'))
+                    .map_or(input.len(), |end| idx + end);
+                idx = input[idx..]
+                    .find(|chr| !(is_ident_char(chr) || chr == '%'))
+                    .map_or(input.len(), |end| idx + end);
+
+                Token::Number(&input[start..idx])
+            }
+            chr if is_ident_char(chr) => {
+                idx = input[idx..]
+                    .find(|chr| !is_ident_char(chr))
+                    .map_or(input.len(), |end| idx + end);
+                let name = &input[start..idx];
+
+                if input[idx..].starts_with('(') {
+                    let end = block_end(input, idx + 1, '(', ')')?;
+                    let arguments = &input[idx + 1..end];
+                    idx = end + 1;
+
+                    Token::Function { name, arguments }
+                } else {
+                    Token::Ident(name)
+                }
+            }
+            _ => return None,
+        };
+
+        tokens.push(token);
+    }
+
+    Some(tokens)
+}
+
+/// Split the input on the separator, ignoring separators inside of strings and blocks
+///
+/// Returns `None` if the input contains unbalanced quotes or parentheses
+fn split_top_level(input: &str, separator: char) -> Option<Vec<&str>> {
+    let mut parts = Vec::new();
+    let mut part_start = 0;
+    let mut idx = 0;
+
+    while let Some(chr) = input[idx..].chars().next() {
+        match chr {
+            '"' | '\'' => {
+                idx = string_end(input, idx)?;
+                continue;
+            }
+            '(' => {
+                idx = block_end(input, idx + 1, '(', ')')? + 1;
+                continue;
+            }
+            ')' => return None,
+            chr if chr == separator => {
+                parts.push(&input[part_start..idx]);
+                part_start = idx + 1;
+            }
+            _ => {}
+        }
+
+        idx += chr.len_utf8();
+    }
+
+    parts.push(&input[part_start..]);
+    Some(parts)
+}
+
+/// Replace all comments with a single space (as comments act as token separators)
+fn strip_comments(input: &str) -> String {
+    let mut acc = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find("/*") {
+        acc.push_str(&rest[..start]);
+        acc.push(' ');
+
+        let Some(end) = rest[start + 2..].find("*/") else {
+            return acc;
+        };
+        rest = &rest[start + 2 + end + 2..];
+    }
+
+    acc.push_str(rest);
+    acc
+}
+
+#[inline]
+fn is_media_query(condition: &str) -> bool {
+    condition
+        .chars()
+        .all(|chr| chr.is_ascii_alphanumeric() || chr.is_whitespace() || "(),-./:".contains(chr))
+}
+
+#[inline]
+fn is_length(value: &str) -> bool {
+    const UNITS: &[&str] = &[
+        "%", "ch", "cm", "em", "ex", "in", "mm", "pc", "pt", "px", "rem", "vh", "vmax", "vmin",
+        "vw",
+    ];
+
+    let unit_start = value
+        .find(|chr: char| !(chr.is_ascii_digit() || matches!(chr, '.' | '+' | '-')))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+
+    if number.parse::<f64>().is_err() {
+        return false;
+    }
+
+    (unit.is_empty()
+        && number
+            .trim_start_matches(['+', '-'])
+            .trim_matches(['0', '.'])
+            .is_empty())
+        || UNITS
+            .iter()
+            .any(|allowed| unit.eq_ignore_ascii_case(allowed))
+}
+
+impl CssSanitizer<'_> {
+    fn check_function(
+        &self,
+        name: &str,
+        arguments: &str,
+        allowed_url_schemes: &HashSet<&str>,
+    ) -> bool {
+        let name = name.to_ascii_lowercase();
+        if !self.allowed_functions.contains(name.as_str()) {
+            return false;
+        }
+
+        if name == "url" {
+            let url = arguments.trim().trim_matches(['"', '\'']);
+            return url_scheme(url).is_some_and(|scheme| allowed_url_schemes.contains(scheme));
+        }
+
+        self.check_tokens(arguments, allowed_url_schemes).is_some()
+    }
+
+    fn check_tokens<'s>(
+        &self,
+        value: &'s str,
+        allowed_url_schemes: &HashSet<&str>,
+    ) -> Option<Vec<Token<'s>>> {
+        let tokens = tokenize(value)?;
+
+        for token in &tokens {
+            if let Token::Function { name, arguments } = token {
+                if !self.check_function(name, arguments, allowed_url_schemes) {
+                    return None;
+                }
+            }
+        }
+
+        Some(tokens)
+    }
+
+    fn check_value(
+        &self,
+        expected: &CssValue<'_>,
+        value: &str,
+        allowed_url_schemes: &HashSet<&str>,
+    ) -> bool {
+        let Some(tokens) = self.check_tokens(value, allowed_url_schemes) else {
+            return false;
+        };
+
+        match expected {
+            CssValue::Any => true,
+            CssValue::Color => match tokens.as_slice() {
+                [Token::Hash(hex)] => {
+                    matches!(hex.len(), 3 | 4 | 6 | 8)
+                        && hex.chars().all(|chr| chr.is_ascii_hexdigit())
+                }
+                [Token::Ident(name)] => name.chars().all(|chr| chr.is_ascii_alphabetic()),
+                [Token::Function { name, arguments }] => {
+                    ["rgb", "rgba", "hsl", "hsla"]
+                        .iter()
+                        .any(|function| name.eq_ignore_ascii_case(function))
+                        && tokenize(arguments).is_some_and(|arguments| {
+                            arguments
+                                .iter()
+                                .all(|token| matches!(token, Token::Number(..) | Token::Delimiter))
+                        })
+                }
+                _ => false,
+            },
+            CssValue::Length => {
+                (1..=4).contains(&tokens.len())
+                    && tokens
+                        .iter()
+                        .all(|token| matches!(token, Token::Number(number) if is_length(number)))
+            }
+            CssValue::Keywords(keywords) => {
+                !tokens.is_empty()
+                    && tokens.iter().all(|token| match token {
+                        Token::Ident(keyword) | Token::Number(keyword) => {
+                            keywords.contains(keyword.to_ascii_lowercase().as_str())
+                        }
+                        _ => false,
+                    })
+            }
+        }
+    }
+
+    /// Sanitize a single declaration and append it to the accumulator
+    fn sanitize_declaration(
+        &self,
+        acc: &mut String,
+        declaration: &str,
+        allowed_url_schemes: &HashSet<&str>,
+    ) {
+        // Opening angle brackets would allow breaking out of `<style>` tags
+        if declaration.contains(['\\', '<']) {
+            return;
+        }
+
+        let Some((property, value)) = declaration.split_once(':') else {
+            return;
+        };
+
+        let property = property.trim().to_ascii_lowercase();
+        let mut value = value.trim();
+
+        let important = match value.rsplit_once('!') {
+            Some((rest, flag)) if flag.trim().eq_ignore_ascii_case("important") => {
+                value = rest.trim_end();
+                true
+            }
+            _ => false,
+        };
+
+        if DENIED_PROPERTIES.contains(&property.as_str()) {
+            return;
+        }
+
+        let Some(expected) = self.allowed_properties.get(property.as_str()) else {
+            return;
+        };
+
+        if !self.check_value(expected, value, allowed_url_schemes) {
+            return;
+        }
+
+        if !acc.is_empty() {
+            acc.push(' ');
+        }
+
+        let _ = write!(acc, "{property}: {value}");
+        if important {
+            acc.push_str(" !important");
+        }
+        acc.push(';');
+    }
+
+    /// Sanitize a list of declarations, as found in `style` attributes
+    ///
+    /// Declarations that don't pass the allow-list are discarded
+    #[must_use]
+    pub fn sanitize_declarations(
+        &self,
+        input: &str,
+        allowed_url_schemes: &HashSet<&str>,
+    ) -> String {
+        let input = strip_comments(input);
+        let mut acc = String::new();
+
+        let Some(declarations) = split_top_level(&input, ';') else {
+            return acc;
+        };
+
+        for declaration in declarations {
+            self.sanitize_declaration(&mut acc, declaration, allowed_url_schemes);
+        }
+
+        acc
+    }
+
+    fn sanitize_rules(
+        &self,
+        acc: &mut String,
+        input: &str,
+        scope: Option<&str>,
+        allowed_url_schemes: &HashSet<&str>,
+    ) {
+        let mut rest = input;
+
+        loop {
+            rest = rest.trim_start();
+            let Some(prelude_end) = prelude_end(rest) else {
+                break;
+            };
+
+            let prelude = rest[..prelude_end].trim();
+
+            // Statements without a block (such as `@import`) are discarded entirely
+            if rest[prelude_end..].starts_with(';') {
+                rest = &rest[prelude_end + 1..];
+                continue;
+            }
+
+            let Some(end) = block_end(rest, prelude_end + 1, '{', '}') else {
+                break;
+            };
+
+            let block = &rest[prelude_end + 1..end];
+            rest = &rest[end + 1..];
+
+            if let Some(at_rule) = prelude.strip_prefix('@') {
+                let name_end = at_rule
+                    .find(|chr| !is_ident_char(chr))
+                    .unwrap_or(at_rule.len());
+                let (name, condition) = at_rule.split_at(name_end);
+
+                if name.eq_ignore_ascii_case("media") && is_media_query(condition) {
+                    let mut rules = String::new();
+                    self.sanitize_rules(&mut rules, block, scope, allowed_url_schemes);
+
+                    if !rules.is_empty() {
+                        let _ = writeln!(acc, "@media {} {{\n{rules}}}", condition.trim());
+                    }
+                }
+
+                continue;
+            }
+
+            let Some(selectors) = sanitize_selectors(prelude, scope) else {
+                continue;
+            };
+
+            let declarations = self.sanitize_declarations(block, allowed_url_schemes);
+            if !declarations.is_empty() {
+                let _ = writeln!(acc, "{selectors} {{ {declarations} }}");
+            }
+        }
+    }
+
+    /// Sanitize a style sheet, as found in `<style>` tags
+    ///
+    /// - Rules with selectors containing escape sequences or angle brackets are discarded
+    /// - `@media` rules are sanitized recursively, all other at-rules (such as `@import`) are discarded
+    /// - If a scope is provided, all selectors are prefixed with it as a class selector
+    #[must_use]
+    pub fn sanitize_stylesheet(
+        &self,
+        input: &str,
+        scope: Option<&str>,
+        allowed_url_schemes: &HashSet<&str>,
+    ) -> String {
+        let input = strip_comments(input)
+            .replace("<!--", " ")
+            .replace("-->", " ");
+
+        let mut acc = String::new();
+        self.sanitize_rules(&mut acc, &input, scope, allowed_url_schemes);
+
+        acc
+    }
+}
+
+/// Check whether the selector only matches descendants of the scope class
+///
+/// Sibling combinators (`.scope ~ p`) match elements outside of the scope, so only the descendant combinator counts
+#[inline]
+fn is_scoped(selector: &str, scope: &str) -> bool {
+    selector
+        .strip_prefix('.')
+        .and_then(|rest| rest.strip_prefix(scope))
+        .and_then(|rest| rest.strip_prefix(' '))
+        .is_some_and(|rest| {
+            let rest = rest.trim_start();
+            !rest.is_empty() && !rest.starts_with(['>', '~', '+', '|'])
+        })
+}
+
+fn sanitize_selectors(selectors: &str, scope: Option<&str>) -> Option<String> {
+    if selectors.is_empty() || selectors.contains(['\\', '<', '@', '{', '}', ';']) {
+        return None;
+    }
+
+    let mut acc = String::new();
+    for selector in split_top_level(selectors, ',')? {
+        let selector = selector.trim();
+        if selector.is_empty() {
+            return None;
+        }
+
+        if !acc.is_empty() {
+            acc.push_str(", ");
+        }
+
+        match scope {
+            // Don't scope selectors twice, so sanitizing our own output doesn't change it
+            Some(scope) if !is_scoped(selector, scope) => {
+                let _ = write!(acc, ".{scope} {selector}");
+            }
+            _ => acc.push_str(selector),
+        }
+    }
+
+    Some(acc)
+}
+
+impl Default for CssSanitizer<'static> {
+    fn default() -> Self {
+        let allowed_functions = hashset!["hsl", "hsla", "rgb", "rgba"];
+        let allowed_properties = hashmap![
+            "background-color" => CssValue::Color,
+            "color" => CssValue::Color,
+            "font-style" => CssValue::Keywords(hashset![
+                "italic", "normal", "oblique",
+            ]),
+            "font-weight" => CssValue::Keywords(hashset![
+                "bold", "bolder", "lighter", "normal",
+                "100", "200", "300", "400", "500", "600", "700", "800", "900",
+            ]),
+            "text-align" => CssValue::Keywords(hashset![
+                "center", "end", "justify", "left", "right", "start",
+            ]),
+            "text-decoration" => CssValue::Keywords(hashset![
+                "line-through", "none", "overline", "underline",
+            ]),
+        ];
+
+        Self {
+            allowed_functions,
+            allowed_properties,
+        }
+    }
+}
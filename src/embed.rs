@@ -0,0 +1,139 @@
+use crate::hashset;
+use ahash::HashSet;
+use url::Url;
+
+/// Attributes that are allowed on `iframe` tags if an embed policy is set
+pub(crate) const EMBED_ATTRIBUTES: &[&str] = &[
+    "allow",
+    "allowfullscreen",
+    "height",
+    "loading",
+    "referrerpolicy",
+    "sandbox",
+    "src",
+    "title",
+    "width",
+];
+
+/// Policy for embedding content via `iframe` tags
+///
+/// If set, `iframe` tags are allowed as long as their `src` matches one of the allowed sources.
+/// All other `iframe` tags are removed including their content
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmbedPolicy<'a> {
+    /// Permissions you want to allow in the `allow` attribute (for example, `fullscreen`)
+    ///
+    /// The origin lists of the permissions are dropped, meaning they default to the origin of the embed
+    pub allowed_permissions: HashSet<&'a str>,
+
+    /// Patterns the `src` of an `iframe` has to match
+    ///
+    /// A pattern consists of a host and an optional path prefix (for example, `www.youtube-nocookie.com/embed/`).
+    /// Hosts starting with `*.` match all subdomains of the host. Hosts are compared case-insensitively, paths aren't
+    ///
+    /// Since `PeerTube` is self-hosted, you have to add the instances you trust (for example, `framatube.org/videos/embed/`)
+    pub allowed_sources: HashSet<&'a str>,
+
+    /// Value of the `referrerpolicy` attribute forced on all embeds
+    pub referrer_policy: &'a str,
+
+    /// Tokens of the `sandbox` attribute forced on all embeds
+    pub sandbox: Vec<&'a str>,
+}
+
+/// Check whether the value contains something that looks like a character reference (for example, `&sol;` or `&#47;`)
+#[inline]
+fn has_character_reference(value: &str) -> bool {
+    value.match_indices('&').any(|(start, _)| {
+        let reference = &value[start + 1..];
+        let name = reference.strip_prefix('#').unwrap_or(reference);
+        let len = name
+            .find(|chr: char| !chr.is_ascii_alphanumeric())
+            .unwrap_or(name.len());
+        len > 0 && name[len..].starts_with(';')
+    })
+}
+
+impl EmbedPolicy<'_> {
+    /// Check whether the URL matches one of the allowed sources
+    ///
+    /// The URL has to be decoded already. URLs with credentials or leftover character references are never allowed
+    #[must_use]
+    pub fn is_allowed_source(&self, url: &str) -> bool {
+        if has_character_reference(url) {
+            return false;
+        }
+
+        let Ok(url) = Url::parse(url) else {
+            return false;
+        };
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        if !url.username().is_empty() || url.password().is_some() {
+            return false;
+        }
+
+        // The parser resolves dot segments and backslashes, so the path can't escape the prefix
+        let path = url.path();
+        self.allowed_sources.iter().any(|pattern| {
+            let (host_pattern, path_prefix) = pattern
+                .split_once('/')
+                .map_or((*pattern, ""), |(host, path)| (host, path));
+
+            let host_matches = match host_pattern.strip_prefix("*.") {
+                Some(domain) => host
+                    .len()
+                    .checked_sub(domain.len() + 1)
+                    .is_some_and(|start| {
+                        host.as_bytes()[start] == b'.'
+                            && host[start + 1..].eq_ignore_ascii_case(domain)
+                    }),
+                None => host.eq_ignore_ascii_case(host_pattern),
+            };
+
+            host_matches
+                && path
+                    .strip_prefix('/')
+                    .unwrap_or(path)
+                    .starts_with(path_prefix)
+        })
+    }
+
+    /// Filter the value of an `allow` attribute down to the allowed permissions
+    #[must_use]
+    pub fn filter_permissions(&self, allow: &str) -> String {
+        allow
+            .split(';')
+            .filter_map(|directive| directive.split_ascii_whitespace().next())
+            .filter(|permission| self.allowed_permissions.contains(permission))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl Default for EmbedPolicy<'static> {
+    fn default() -> Self {
+        Self {
+            allowed_permissions: hashset![
+                "autoplay",
+                "clipboard-write",
+                "encrypted-media",
+                "fullscreen",
+                "picture-in-picture",
+            ],
+            allowed_sources: hashset![
+                "player.vimeo.com/video/",
+                "www.youtube-nocookie.com/embed/",
+                "www.youtube.com/embed/",
+            ],
+            referrer_policy: "strict-origin-when-cross-origin",
+            sandbox: vec![
+                "allow-popups",
+                "allow-presentation",
+                "allow-same-origin",
+                "allow-scripts",
+            ],
+        }
+    }
+}
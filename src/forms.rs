@@ -0,0 +1,83 @@
+use crate::{entities, hashset};
+use ahash::HashSet;
+use lol_html::{html_content::Element, HandlerResult};
+
+/// Attributes that are allowed on `input` tags if a form policy is set
+pub(crate) const INPUT_ATTRIBUTES: &[&str] = &["checked", "disabled", "type"];
+
+/// Attributes that submit forms or move the focus, removed from all elements if a form policy is set
+const SUBMIT_ATTRIBUTES: &[&str] = &[
+    "action",
+    "autofocus",
+    "form",
+    "formaction",
+    "formenctype",
+    "formmethod",
+    "formnovalidate",
+    "formtarget",
+    "method",
+];
+
+/// Policy for rendering read-only form controls (for example, task lists)
+///
+/// If set, `input` tags are allowed as long as their type is one of the allowed types. They are always disabled, so they can't be changed or submitted.
+/// All other `input` tags are removed.
+///
+/// Forms never submit: `form` tags are removed while keeping their content, `button` tags (if allowed) are turned into plain buttons,
+/// and attributes like `formaction` and `autofocus` are removed from all elements
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormPolicy<'a> {
+    /// Types of `input` tags you want to keep (for example, `checkbox`)
+    ///
+    /// Types are compared case-insensitively. Inputs without a type are text inputs
+    pub allowed_input_types: HashSet<&'a str>,
+}
+
+impl FormPolicy<'_> {
+    /// Check whether `input` tags of the type are allowed
+    #[must_use]
+    pub fn is_allowed_input_type(&self, input_type: &str) -> bool {
+        self.allowed_input_types
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(input_type))
+    }
+
+    /// Remove the attributes that submit forms, and the inputs of types that aren't allowed
+    pub(crate) fn clean_element(
+        &self,
+        element: &mut Element<'_, '_>,
+        tag_name: &str,
+    ) -> HandlerResult {
+        for name in SUBMIT_ATTRIBUTES {
+            element.remove_attribute(name);
+        }
+
+        match tag_name {
+            "button" => element.set_attribute("type", "button")?,
+            "input" => {
+                // Browsers don't trim the type, so `" checkbox"` renders as a text input
+                let input_type = element.get_attribute("type").map_or_else(
+                    || "text".to_string(),
+                    |input_type| entities::decode(&input_type).into_owned(),
+                );
+
+                if self.is_allowed_input_type(&input_type) {
+                    element.set_attribute("disabled", "")?;
+                } else {
+                    element.remove();
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for FormPolicy<'static> {
+    fn default() -> Self {
+        Self {
+            allowed_input_types: hashset!["checkbox"],
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{escape_attribute, namespace::Namespace, unwrap_element, BubbleBath};
+use lol_html::{
+    errors::AttributeNameError,
+    html_content::{ContentType, Element},
+    HandlerResult,
+};
+use std::fmt::Write;
+
+/// Hook you can register on [`BubbleBath::element_hooks`]
+///
+/// Runs on the start tag, so it can't see the content of the element
+pub type ElementHook<'a> =
+    Box<dyn Fn(&mut ElementView<'_, '_, '_>) -> HandlerResult + Send + Sync + 'a>;
+
+/// Hook you can register on [`BubbleBath::text_hook`]
+///
+/// Receives the decoded content of a whole text node and the names of the elements it is inside of (outermost first).
+/// Returns the new content of the text node
+pub type TextHook<'a> = Box<dyn Fn(&str, &[String]) -> String + Send + Sync + 'a>;
+
+/// Restricted view of an element, handed to element hooks
+///
+/// The view can't be used to add attributes that aren't allowed on the element, and it only inserts text or elements the policy allows
+pub struct ElementView<'v, 'r, 't> {
+    bubble_bath: &'v BubbleBath<'v>,
+    content_removed: bool,
+    element: &'v mut Element<'r, 't>,
+    namespace: Namespace,
+    removed: bool,
+    tag_name: &'v str,
+}
+
+impl<'v, 'r, 't> ElementView<'v, 'r, 't> {
+    pub(crate) fn new(
+        element: &'v mut Element<'r, 't>,
+        bubble_bath: &'v BubbleBath<'v>,
+        namespace: Namespace,
+        tag_name: &'v str,
+    ) -> Self {
+        Self {
+            bubble_bath,
+            content_removed: false,
+            element,
+            namespace,
+            removed: false,
+            tag_name,
+        }
+    }
+
+    pub(crate) fn is_content_removed(&self) -> bool {
+        self.content_removed
+    }
+
+    pub(crate) fn is_removed(&self) -> bool {
+        self.removed
+    }
+
+    /// Name of the tag (after it was renamed)
+    #[inline]
+    #[must_use]
+    pub fn tag_name(&self) -> &str {
+        self.tag_name
+    }
+
+    /// Value of the attribute, if the element has it
+    #[inline]
+    #[must_use]
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        self.element.get_attribute(name)
+    }
+
+    /// Whether the element has the attribute
+    #[inline]
+    #[must_use]
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.element.has_attribute(name)
+    }
+
+    /// Set the attribute on the element
+    ///
+    /// Attributes that aren't allowed on the element are silently ignored
+    ///
+    /// # Errors
+    ///
+    /// The name of the attribute is invalid
+    #[inline]
+    pub fn set_attribute(&mut self, name: &str, value: &str) -> Result<(), AttributeNameError> {
+        if self
+            .bubble_bath
+            .is_attribute_allowed(self.namespace, self.tag_name, name)
+        {
+            self.element.set_attribute(name, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove the attribute from the element
+    #[inline]
+    pub fn remove_attribute(&mut self, name: &str) {
+        self.element.remove_attribute(name);
+    }
+
+    /// Insert text before the element
+    #[inline]
+    pub fn before(&mut self, text: &str) {
+        self.element.before(text, ContentType::Text);
+    }
+
+    /// Insert text after the element
+    #[inline]
+    pub fn after(&mut self, text: &str) {
+        self.element.after(text, ContentType::Text);
+    }
+
+    /// Insert text right after the start tag of the element
+    #[inline]
+    pub fn prepend(&mut self, text: &str) {
+        self.element.prepend(text, ContentType::Text);
+    }
+
+    /// Insert text right before the end tag of the element
+    #[inline]
+    pub fn append(&mut self, text: &str) {
+        self.element.append(text, ContentType::Text);
+    }
+
+    /// Wrap the element in a new element with the provided attributes
+    ///
+    /// Returns `false` if the tag isn't allowed, leaving the element as is.
+    /// Attributes that aren't allowed on the tag are silently ignored, and so are attributes that are cleaned as URLs
+    #[inline]
+    pub fn wrap(&mut self, tag_name: &str, attributes: &[(&str, &str)]) -> bool {
+        if !self.bubble_bath.allowed_tags.contains(tag_name) {
+            return false;
+        }
+
+        let url_attributes = self.bubble_bath.clean_url_attributes.get(tag_name);
+        let mut start_tag = format!("<{tag_name}");
+        for (name, value) in attributes {
+            if !self
+                .bubble_bath
+                .is_attribute_allowed(Namespace::Html, tag_name, name)
+                || url_attributes.is_some_and(|url_attributes| url_attributes.contains(name))
+            {
+                continue;
+            }
+
+            let _ = write!(start_tag, " {name}=\"{}\"", escape_attribute(value));
+        }
+        start_tag.push('>');
+
+        self.element.before(&start_tag, ContentType::Html);
+        self.element
+            .after(&format!("</{tag_name}>"), ContentType::Html);
+        true
+    }
+
+    /// Remove the element and its content
+    #[inline]
+    pub fn remove(&mut self) {
+        self.element.remove();
+        self.content_removed = true;
+        self.removed = true;
+    }
+
+    /// Remove the element but keep its content
+    #[inline]
+    pub fn remove_and_keep_content(&mut self) {
+        unwrap_element(self.element);
+        self.removed = true;
+    }
+}
@@ -0,0 +1,127 @@
+use crate::links::host_range;
+use unicode_security::{skeleton, MixedScript};
+
+/// What to do with links pointing to suspicious hosts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SuspiciousHosts<'a> {
+    /// Keep the link and set the attribute on the element (for example, a class to warn the user)
+    ///
+    /// The attribute is only set if it's allowed on the element
+    Flag {
+        /// Name of the attribute
+        name: &'a str,
+
+        /// Value of the attribute
+        value: &'a str,
+    },
+
+    /// Remove the URL
+    Reject,
+}
+
+/// Policy for the hosts of the URLs we clean
+///
+/// All hosts are converted to punycode. A host is considered suspicious if one of its labels mixes scripts
+/// or only consists of characters that are confusable with ASCII characters (for example, `раура1.com` written in Cyrillic)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HostPolicy<'a> {
+    /// Replace the text of links that display a different domain than the one they point to with the URL
+    ///
+    /// Only the text nodes directly inside of the `a` tag are checked
+    pub rewrite_link_text: bool,
+
+    /// What to do with links pointing to suspicious hosts
+    pub suspicious_hosts: SuspiciousHosts<'a>,
+}
+
+impl Default for HostPolicy<'static> {
+    fn default() -> Self {
+        Self {
+            rewrite_link_text: false,
+            suspicious_hosts: SuspiciousHosts::Reject,
+        }
+    }
+}
+
+/// Result of checking a host
+pub(crate) struct CheckedHost {
+    /// Punycode representation of the host (`None` if the host isn't a valid domain name)
+    pub ascii: Option<String>,
+
+    /// Whether the host is suspicious
+    pub suspicious: bool,
+}
+
+#[inline]
+fn is_label_suspicious(label: &str) -> bool {
+    if label.is_ascii() {
+        return false;
+    }
+
+    !label.is_single_script() || skeleton(label).all(|chr| chr.is_ascii())
+}
+
+/// Convert the host to punycode and check whether it is suspicious
+#[inline]
+pub(crate) fn check_host(host: &str) -> CheckedHost {
+    match idna::domain_to_ascii(host) {
+        Ok(ascii) => {
+            let unicode = idna::domain_to_unicode(&ascii).0;
+            let suspicious = unicode.split('.').any(is_label_suspicious);
+
+            CheckedHost {
+                ascii: Some(ascii),
+                suspicious,
+            }
+        }
+        Err(..) => CheckedHost {
+            ascii: None,
+            suspicious: true,
+        },
+    }
+}
+
+/// Host the text displays, if it looks like a URL or a domain
+#[inline]
+fn displayed_host(text: &str) -> Option<&str> {
+    let text = text.trim();
+
+    if let Some(range) = host_range(text) {
+        return Some(&text[range]);
+    }
+
+    let domain = text.split(['/', '?', '#']).next()?;
+    let labels = domain.split('.').collect::<Vec<_>>();
+    let looks_like_domain = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty() && label.chars().all(|chr| chr.is_alphanumeric() || chr == '-')
+        })
+        && labels
+            .last()
+            .is_some_and(|tld| tld.chars().all(char::is_alphabetic));
+
+    looks_like_domain.then_some(domain)
+}
+
+/// Compare two hosts, ignoring case, IDN encoding, and a `www.` prefix
+#[inline]
+fn same_host(left: &str, right: &str) -> bool {
+    let normalize = |host: &str| {
+        let host = idna::domain_to_ascii(host).unwrap_or_else(|_| host.to_lowercase());
+        host.strip_prefix("www.")
+            .map(str::to_string)
+            .unwrap_or(host)
+    };
+
+    normalize(left) == normalize(right)
+}
+
+/// Check whether the text of a link displays a different host than the URL points to
+#[inline]
+pub(crate) fn is_misleading(text: &str, url: &str) -> bool {
+    let Some(displayed) = displayed_host(text) else {
+        return false;
+    };
+
+    host_range(url).map_or(true, |range| !same_host(displayed, &url[range]))
+}
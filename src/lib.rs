@@ -3,21 +3,72 @@
 //! For an entry point to the library, check the docs of [`BubbleBath`] or [`clean`]
 //!
 
+use self::linkify::LinkKind;
 use ahash::{HashMap, HashSet};
 use lol_html::{
     errors::RewritingError,
-    html_content::{Comment, ContentType, DocumentEnd, Element, TextChunk},
-    DocumentContentHandlers, ElementContentHandlers, HandlerResult, HtmlRewriter, Selector,
-    Settings,
+    html_content::{Attribute, Comment, ContentType, DocumentEnd, Element, TextChunk, TextType},
+    DocumentContentHandlers, ElementContentHandlers, HtmlRewriter, Selector, Settings,
 };
 use once_cell::sync::Lazy;
-use slab::Slab;
-use std::{borrow::Cow, cell::RefCell, fmt::Write, iter, rc::Rc, str::FromStr};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    fmt::Write,
+    iter,
+    rc::Rc,
+    str::FromStr,
+};
 use thiserror::Error;
 
-pub use lol_html::MemorySettings;
-
+#[cfg(feature = "idn")]
+pub use self::idn::{HostPolicy, SuspiciousHosts};
+pub use self::{
+    content_model::ContentModel,
+    css::{CssSanitizer, CssValue},
+    embed::EmbedPolicy,
+    forms::FormPolicy,
+    hooks::{ElementHook, ElementView, TextHook},
+    links::ExternalLinks,
+    namespace::Namespace,
+    svg::SvgPolicy,
+    tables::TableRepair,
+    tokens::{recognize as recognize_token, Token, TokenLinks, TokenRecognizer},
+    unicode::UnicodeFilter,
+};
+pub use lol_html::{HandlerResult, MemorySettings};
+
+mod cleanup;
+mod compose;
+mod content_model;
+mod css;
+mod embed;
+mod entities;
+mod forms;
+mod hooks;
+#[cfg(feature = "idn")]
+mod idn;
+mod linkify;
+mod links;
 mod macros;
+mod namespace;
+mod presets;
+mod svg;
+mod tables;
+mod tokens;
+mod unicode;
+
+/// Well-known DOM properties that could be shadowed by `id` and `name` attributes
+#[rustfmt::skip]
+const CLOBBERABLE_NAMES: &[&str] = &[
+    "action", "alert", "attributes", "body", "children", "cookie", "createElement",
+    "defaultView", "document", "documentElement", "domain", "elements", "eval", "fetch",
+    "forms", "frames", "getElementById", "getElementsByName", "getElementsByTagName", "head",
+    "images", "implementation", "innerHTML", "links", "localStorage", "location", "method",
+    "name", "nodeName", "nodeType", "opener", "outerHTML", "ownerDocument", "parent",
+    "parentNode", "querySelector", "querySelectorAll", "referrer", "scripts", "self",
+    "sessionStorage", "setTimeout", "style", "submit", "top", "window",
+];
 
 static GLOBAL_BUBBLE_BATH: Lazy<BubbleBath<'static>> = Lazy::new(BubbleBath::default);
 static SELECT_ALL: Lazy<Selector> = Lazy::new(|| Selector::from_str("*").unwrap());
@@ -36,6 +87,99 @@ pub fn clean(content: &str) -> Result<String, Error> {
     GLOBAL_BUBBLE_BATH.clean(content)
 }
 
+/// Extract the scheme of an absolute URL
+///
+/// Like in the URL standard, the scheme starts with an ASCII letter, followed by ASCII letters, digits, `+`, `-` or `.`, and ends with a colon.
+/// This includes schemes without an authority (for example, `mailto:`)
+#[inline]
+fn url_scheme(url: &str) -> Option<&str> {
+    let (scheme, _rest) = url.split_once(':')?;
+    let mut chars = scheme.chars();
+
+    let valid = chars.next().is_some_and(|chr| chr.is_ascii_alphabetic())
+        && chars.all(|chr| chr.is_ascii_alphanumeric() || matches!(chr, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+/// Split the value of a `srcset` attribute into the URLs of its image candidates
+///
+/// Returns `None` if the descriptors of a candidate can't be parsed
+#[inline]
+fn srcset_urls(srcset: &str) -> Option<Vec<&str>> {
+    let mut urls = Vec::new();
+    let mut rest = srcset;
+
+    loop {
+        rest = rest.trim_start_matches(|chr: char| chr.is_ascii_whitespace() || chr == ',');
+        if rest.is_empty() {
+            return Some(urls);
+        }
+
+        let url_end = rest
+            .find(|chr: char| chr.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let url = &rest[..url_end];
+        rest = &rest[url_end..];
+
+        // Trailing commas end the candidate without any descriptors
+        if url.ends_with(',') {
+            urls.push(url.trim_end_matches(','));
+            continue;
+        }
+
+        urls.push(url);
+
+        let descriptors_end = rest.find(',').unwrap_or(rest.len());
+        if rest[..descriptors_end].contains('(') {
+            return None;
+        }
+        rest = &rest[descriptors_end..];
+    }
+}
+
+/// Check whether an attribute is in the allow-list
+///
+/// Entries ending with `*` match all attributes starting with the part before the asterisk.
+/// Event handlers (`on*`) are never matched by these wildcards
+#[inline]
+fn attribute_allowed(allowed_attributes: &HashSet<&str>, attribute_name: &str) -> bool {
+    if allowed_attributes.contains(attribute_name) {
+        return true;
+    }
+
+    if attribute_name.starts_with("on") {
+        return false;
+    }
+
+    allowed_attributes.iter().any(|pattern| {
+        pattern
+            .strip_suffix('*')
+            .is_some_and(|prefix| attribute_name.starts_with(prefix))
+    })
+}
+
+/// Merge tokens into a whitespace-separated token list
+///
+/// Existing tokens are kept in their original order, duplicates are dropped (ignoring ASCII case)
+#[inline]
+fn merge_tokens<'t, I>(existing: Option<&'t str>, tokens: I) -> String
+where
+    I: IntoIterator<Item = &'t str>,
+{
+    let mut merged: Vec<&str> = Vec::new();
+    for token in existing
+        .into_iter()
+        .flat_map(str::split_ascii_whitespace)
+        .chain(tokens.into_iter().flat_map(str::split_ascii_whitespace))
+    {
+        if !merged.iter().any(|seen| seen.eq_ignore_ascii_case(token)) {
+            merged.push(token);
+        }
+    }
+
+    merged.join(" ")
+}
+
 #[inline]
 fn clean_text(source: &str) -> String {
     let mut acc = String::with_capacity(source.len());
@@ -62,6 +206,351 @@ fn clean_text(source: &str) -> String {
     acc
 }
 
+/// Remove the element but keep its content
+///
+/// We can't use `Element::remove_and_keep_content` since `lol_html` would also remove the end tag of the ancestor
+/// if the element is closed implicitly
+#[inline]
+fn unwrap_element(element: &mut Element<'_, '_>) {
+    let source_name = element.tag_name();
+    element.start_tag().remove();
+
+    if let Some(end_tag_handlers) = element.end_tag_handlers() {
+        end_tag_handlers.push(Box::new(move |end_tag| {
+            if end_tag.name() == source_name {
+                end_tag.remove();
+            }
+
+            Ok(())
+        }));
+    }
+}
+
+/// Start tag of the element with its current attributes
+#[inline]
+fn start_tag(element: &Element<'_, '_>, tag_name: &str) -> String {
+    let mut start_tag = format!("<{tag_name}");
+    for attribute in element.attributes() {
+        let _ = write!(
+            start_tag,
+            " {}=\"{}\"",
+            attribute.name_preserve_case(),
+            attribute.value().replace('"', "&quot;")
+        );
+    }
+
+    if element.is_self_closing() {
+        start_tag.push_str(" />");
+    } else {
+        start_tag.push('>');
+    }
+
+    start_tag
+}
+
+/// Escape the value of an attribute
+#[inline]
+fn escape_attribute(source: &str) -> String {
+    let mut acc = String::with_capacity(source.len());
+
+    for chr in source.chars() {
+        match chr {
+            '<' => acc.push_str("&lt;"),
+            '>' => acc.push_str("&gt;"),
+            '"' => acc.push_str("&quot;"),
+            '&' => acc.push_str("&amp;"),
+            '\0' => acc.push_str("&#65533;"),
+            _ => acc.push(chr),
+        }
+    }
+
+    acc
+}
+
+/// Attributes sorted by their name, so the output doesn't depend on the iteration order of the map
+#[inline]
+fn sorted_attributes<'v>(attributes: &'v HashMap<&str, &str>) -> Vec<(&'v str, &'v str)> {
+    let mut attributes: Vec<_> = attributes
+        .iter()
+        .map(|(name, value)| (*name, *value))
+        .collect();
+    attributes.sort_unstable();
+    attributes
+}
+
+/// Level of the heading, if the tag is one
+#[inline]
+fn heading_level(tag_name: &str) -> Option<u8> {
+    match tag_name.as_bytes() {
+        [b'h', level @ b'1'..=b'6'] => Some(level - b'0'),
+        _ => None,
+    }
+}
+
+/// Attributes we can read and write
+///
+/// Implemented for elements and for the attributes of links we write ourselves
+trait Attributes {
+    /// Get the value of the attribute
+    fn get(&self, name: &str) -> Option<String>;
+
+    /// Set the value of the attribute and move it to the end
+    ///
+    /// Moving it keeps the order of the attributes the same when our own output is sanitized again
+    fn set(&mut self, name: &str, value: &str) -> HandlerResult;
+}
+
+impl Attributes for Element<'_, '_> {
+    #[inline]
+    fn get(&self, name: &str) -> Option<String> {
+        self.get_attribute(name)
+    }
+
+    #[inline]
+    fn set(&mut self, name: &str, value: &str) -> HandlerResult {
+        self.remove_attribute(name);
+        self.set_attribute(name, value)?;
+        Ok(())
+    }
+}
+
+impl Attributes for Vec<(String, String)> {
+    #[inline]
+    fn get(&self, name: &str) -> Option<String> {
+        self.iter()
+            .find(|(attribute_name, _value)| attribute_name == name)
+            .map(|(_name, value)| value.clone())
+    }
+
+    #[inline]
+    fn set(&mut self, name: &str, value: &str) -> HandlerResult {
+        self.retain(|(attribute_name, _value)| attribute_name != name);
+        self.push((name.to_string(), value.to_string()));
+
+        Ok(())
+    }
+}
+
+/// State of a single sanitization run, shared between the handlers
+#[derive(Default)]
+struct State {
+    /// Names of the elements we are currently inside of (outermost first), only counting elements we kept
+    elements: RefCell<Vec<String>>,
+
+    /// Number of child elements we kept for each of the elements we are currently inside of, in the same order as `elements`
+    children: RefCell<Vec<usize>>,
+
+    /// Sequence numbers of the elements we are currently inside of, in the same order as `elements`
+    ids: RefCell<Vec<usize>>,
+
+    /// Sequence number of the next element we enter, used to tell apart elements at the same depth
+    next_id: Cell<usize>,
+
+    /// Namespaces of the elements we are currently inside of, in the same order as `elements`
+    namespaces: RefCell<Vec<Namespace>>,
+
+    /// Depths and start tags of the elements we haven't emitted yet, since they might end up empty (only used if empty tags are removed)
+    deferred_start_tags: RefCell<Vec<(usize, String)>>,
+
+    /// Whether we are sanitizing our own output again, which skips the transforms that aren't idempotent by design
+    verifying: bool,
+
+    /// Depth and decoded URL of the link we are currently inside of (only tracked if link texts are rewritten)
+    link: RefCell<Option<(usize, String)>>,
+
+    /// Number of `br` tags we emitted since the last other content
+    line_breaks: Cell<usize>,
+
+    /// Level of the last heading we emitted (only tracked if the heading levels are normalized)
+    last_heading_level: Cell<u8>,
+
+    /// Number of elements we are currently inside of that are removed including their content
+    removed_content: Cell<usize>,
+
+    /// Content of the sanitized `<style>` tag we are currently inside of
+    style_sheet: RefCell<Option<String>>,
+
+    /// Content of the text node we are currently buffering
+    text: RefCell<String>,
+
+    /// Start of a character reference at the end of the last chunk of the text node, which we hold back until the next chunk
+    unfinished_reference: RefCell<String>,
+
+    /// Depths of the table wrappers we inserted and haven't closed yet (only tracked if tables are repaired)
+    wrappers: RefCell<Vec<usize>>,
+
+    /// Whether the text we emitted last ends with whitespace, without any tags emitted since (only used if whitespace is collapsed)
+    trailing_whitespace: Cell<bool>,
+
+    /// Tokens we turned into links
+    tokens: RefCell<Vec<Token>>,
+}
+
+impl State {
+    /// Track the element until its end tag is reached
+    #[inline]
+    fn enter_element(
+        self: &Rc<Self>,
+        element: &mut Element<'_, '_>,
+        tag_name: String,
+        namespace: Namespace,
+    ) {
+        let Some(end_tag_handlers) = element.end_tag_handlers() else {
+            return;
+        };
+
+        let id = self.next_id.get();
+        let depth = self.push_element(tag_name.clone(), namespace);
+
+        let state = Rc::clone(self);
+        end_tag_handlers.push(Box::new(move |end_tag| {
+            let own_end_tag = end_tag.name() == tag_name;
+
+            // We closed the element explicitly before (for example, because its end tag was omitted),
+            // so its end tag only closes the elements that were opened after it, which close themselves
+            if state.ids.borrow().get(depth) != Some(&id) {
+                if own_end_tag {
+                    end_tag.remove();
+                }
+
+                return Ok(());
+            }
+
+            let closing = state.close_wrappers(depth);
+            if !closing.is_empty() {
+                end_tag.before(&closing, ContentType::Html);
+            }
+
+            // Elements whose start tag we held back are empty, so we drop them
+            let empty = state.discard_start_tag(depth);
+            if own_end_tag {
+                if empty {
+                    end_tag.remove();
+                }
+            } else if !empty {
+                // The end tag of an element we are inside of closes us implicitly. We might have removed that element,
+                // so we always close ourselves explicitly to keep the content after it from ending up inside of us
+                end_tag.before(&format!("</{tag_name}>"), ContentType::Html);
+            }
+
+            if !empty {
+                state.trailing_whitespace.set(false);
+            }
+
+            state.truncate_elements(depth);
+            Ok(())
+        }));
+    }
+
+    /// Decode the character references in the chunk, if its text type has them
+    ///
+    /// References can be split across chunks, so we hold back a reference at the end of the chunk
+    /// and decode it together with the next chunk of the text node
+    #[inline]
+    fn decode_chunk<'t>(&self, chunk: &'t TextChunk<'_>) -> Cow<'t, str> {
+        if !matches!(chunk.text_type(), TextType::Data | TextType::RCData) {
+            return Cow::Borrowed(chunk.as_str());
+        }
+
+        let mut text = Cow::Borrowed(chunk.as_str());
+
+        let unfinished_reference = self.unfinished_reference.take();
+        if !unfinished_reference.is_empty() {
+            text = Cow::Owned(unfinished_reference + &text);
+        }
+
+        if !chunk.last_in_text_node() {
+            if let Some(start) = entities::unfinished_reference(&text) {
+                *self.unfinished_reference.borrow_mut() = text[start..].to_string();
+                text = Cow::Owned(text[..start].to_string());
+            }
+        }
+
+        match text {
+            Cow::Borrowed(text) => entities::decode_text(text),
+            Cow::Owned(text) => Cow::Owned(entities::decode_text(&text).into_owned()),
+        }
+    }
+
+    /// Start tracking the element as a child of the element we are currently inside of, returning its depth
+    #[inline]
+    fn push_element(&self, tag_name: String, namespace: Namespace) -> usize {
+        let mut elements = self.elements.borrow_mut();
+        let mut children = self.children.borrow_mut();
+        if let Some(count) = children.last_mut() {
+            *count += 1;
+        }
+
+        elements.push(tag_name);
+        children.push(0);
+        self.namespaces.borrow_mut().push(namespace);
+        self.ids.borrow_mut().push(self.next_id.get());
+        self.next_id.set(self.next_id.get() + 1);
+        elements.len() - 1
+    }
+
+    /// Stop tracking all elements from the depth onwards
+    #[inline]
+    fn truncate_elements(&self, depth: usize) {
+        self.elements.borrow_mut().truncate(depth);
+        self.namespaces.borrow_mut().truncate(depth);
+        self.children.borrow_mut().truncate(depth);
+        self.ids.borrow_mut().truncate(depth);
+    }
+
+    /// Namespace of the element we are currently inside of
+    #[inline]
+    fn namespace(&self) -> Namespace {
+        self.namespaces.borrow().last().copied().unwrap_or_default()
+    }
+
+    /// Namespace of a child element of the element we are currently inside of
+    #[inline]
+    fn child_namespace(&self, tag_name: &str) -> Namespace {
+        let elements = self.elements.borrow();
+        let namespaces = self.namespaces.borrow();
+        let parent = elements
+            .last()
+            .zip(namespaces.last())
+            .map(|(name, namespace)| (name.as_str(), *namespace));
+
+        Namespace::of_child(parent, tag_name)
+    }
+
+    /// Track the URL of the link until its end tag is reached
+    #[cfg(feature = "idn")]
+    #[inline]
+    fn enter_link(self: &Rc<Self>, element: &mut Element<'_, '_>, url: String) {
+        let Some(end_tag_handlers) = element.end_tag_handlers() else {
+            return;
+        };
+
+        *self.link.borrow_mut() = Some((self.elements.borrow().len(), url));
+
+        let state = Rc::clone(self);
+        end_tag_handlers.push(Box::new(move |_end_tag| {
+            *state.link.borrow_mut() = None;
+            Ok(())
+        }));
+    }
+
+    /// Track that we are inside of removed content until the end tag of the element is reached
+    #[inline]
+    fn enter_removed_content(self: &Rc<Self>, element: &mut Element<'_, '_>) {
+        let Some(end_tag_handlers) = element.end_tag_handlers() else {
+            return;
+        };
+
+        self.removed_content.set(self.removed_content.get() + 1);
+
+        let state = Rc::clone(self);
+        end_tag_handlers.push(Box::new(move |_end_tag| {
+            state.removed_content.set(state.removed_content.get() - 1);
+            Ok(())
+        }));
+    }
+}
+
 /// Potential errors
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -69,40 +558,164 @@ pub enum Error {
     /// The rewriting of the HTML content failed
     #[error(transparent)]
     Rewriting(#[from] RewritingError),
+
+    /// The output changed when it was sanitized again (see [`BubbleBath::verification`])
+    #[error("sanitized output changed when sanitized again")]
+    Mutation,
 }
 
 /// HTML sanitizer
 ///
 /// `bubble-bath` is allow-list based, meaning all tags are by default cleaned.
 ///
-/// `BubbleBath::default` provides a safe default
+/// `BubbleBath::default` provides a safe default. For common use cases, there are presets like [`BubbleBath::basic_formatting`] or [`BubbleBath::activitypub`]
 ///
 /// ## Implementation details
 ///
 /// - We use `lol_html` as our underlying HTML processor
 /// - Only absolute URLs (i.e. URLs with a scheme) are allowed. Relative links are discarded
+#[allow(clippy::struct_excessive_bools)]
 pub struct BubbleBath<'a> {
+    /// Tags you want to keep in foreign content, keyed by their namespace (for example, `MathML` formulas)
+    ///
+    /// `allowed_tags` only applies to the HTML namespace, entries for [`Namespace::Html`] are ignored.
+    /// In the `MathML` namespace only presentation elements can be allowed, the SVG namespace is checked against `svg_policy` instead if it is set.
+    ///
+    /// Elements that aren't allowed are removed including their content, since their content would otherwise end up in a different namespace.
+    /// The only exception are the `math` and `svg` elements starting the foreign content, which are removed like any other tag
+    pub allowed_foreign_tags: HashMap<Namespace, HashSet<&'a str>>,
+
+    /// Attributes you want to keep on a per-tag basis in foreign content, keyed by their namespace
+    ///
+    /// `allowed_tag_attributes` only applies to the HTML namespace, so an attribute allowed on an HTML tag isn't allowed
+    /// on a foreign tag of the same name (for example, `a` and `title` exist in HTML and SVG). Entries for [`Namespace::Html`] are ignored.
+    /// Supports the same prefix patterns as `allowed_generic_attributes`
+    pub allowed_foreign_tag_attributes: HashMap<Namespace, HashMap<&'a str, HashSet<&'a str>>>,
+
     /// Attributes you want to keep on all tags
+    ///
+    /// Entries ending with `*` act as prefix patterns (for example, `data-*` or `aria-*`).
+    /// Event handlers (`on*`) are never matched by these patterns
     pub allowed_generic_attributes: HashSet<&'a str>,
 
     /// Tags you want to keep
     pub allowed_tags: HashSet<&'a str>,
 
     /// Attributes you want to keep on a per-tag basis
+    ///
+    /// Only applies to the HTML namespace, check `allowed_foreign_tag_attributes` for foreign content.
+    /// Supports the same prefix patterns as `allowed_generic_attributes`
     pub allowed_tag_attributes: HashMap<&'a str, HashSet<&'a str>>,
 
     /// Schemes you want to allow on URLs in anchor tags
     pub allowed_url_schemes: HashSet<&'a str>,
 
     /// Clean certain attributes on tags as if they are URLs
+    ///
+    /// `srcset` attributes are split into their image candidates, and removed if any of the candidates isn't allowed
     pub clean_url_attributes: HashMap<&'a str, HashSet<&'a str>>,
 
+    /// Collapse runs of whitespace in text into a single space
+    ///
+    /// Text inside of `pre` and `code` tags is kept as is
+    pub collapse_whitespace: bool,
+
+    /// Strategy for elements that violate the content model of the elements they are inside of (for example, `li` tags outside of lists)
+    ///
+    /// If set, end tags omitted before a sibling (for example, `li` before the next `li`) are also closed explicitly. Check [`ContentModel`] for details
+    pub content_model: Option<ContentModel>,
+
+    /// Sanitizer for inline styles
+    ///
+    /// If set, `style` attributes are kept on all allowed tags and filtered through the sanitizer.
+    /// Otherwise they are treated like any other attribute
+    pub css_sanitizer: Option<CssSanitizer<'a>>,
+
+    /// Attributes you want to set on a per-tag basis, but only if the tag doesn't have them already
+    ///
+    /// Applied after `set_tag_attributes` (for example, `loading="lazy"` on `img` tags)
+    pub default_tag_attributes: HashMap<&'a str, HashMap<&'a str, &'a str>>,
+
+    /// Hooks you want to run on a per-tag basis
+    ///
+    /// Hooks run after the attributes were checked against the allow-lists and can't add attributes that aren't allowed.
+    /// Values set by hooks are still sanitized (for example, URLs are cleaned afterwards).
+    ///
+    /// Hooks run on the start tag, before the content of the element is parsed, so they only see the tag and its attributes.
+    /// They can unwrap links to a certain host, but not links whose text is their URL
+    pub element_hooks: HashMap<&'a str, Vec<ElementHook<'a>>>,
+
+    /// Policy for embedding content via `iframe` tags (for example, videos hosted on `YouTube`)
+    ///
+    /// If set, `iframe` tags are allowed even if they aren't in `allowed_tags`. Check [`EmbedPolicy`] for details
+    pub embed_policy: Option<EmbedPolicy<'a>>,
+
+    /// Configuration for `a` tags pointing to external hosts
+    ///
+    /// If set, the configured `rel` tokens are merged into the `rel` attribute of external links (including the ones created by `linkify`).
+    /// The `rel` attribute of `a` tags in `set_tag_attributes` is ignored, so internal links keep their allowed `rel` tokens
+    pub external_links: Option<ExternalLinks<'a>>,
+
+    /// Policy for read-only form controls (for example, checkboxes of task lists)
+    ///
+    /// If set, `input` tags are allowed even if they aren't in `allowed_tags`, and forms can't be submitted. Check [`FormPolicy`] for details
+    pub form_policy: Option<FormPolicy<'a>>,
+
+    /// Number of levels you want to shift all headings down by (for example, `1` turns `h1` into `h2`)
+    ///
+    /// Levels are clamped at `h6`. Headings are shifted after renaming and before the allow-lists are checked
+    pub heading_offset: u8,
+
+    /// Policy for the hosts of URLs in attributes listed in `clean_url_attributes`
+    ///
+    /// If set, hosts are converted to punycode and links to suspicious hosts (mixed scripts or confusables) are flagged or removed
+    #[cfg(feature = "idn")]
+    pub host_policy: Option<HostPolicy<'a>>,
+
+    /// Prefix added to all kept `id` and `name` attributes (for example, `user-content-`)
+    ///
+    /// This prevents user content from clobbering globals or colliding with the IDs of your page.
+    /// Values that would shadow well-known DOM properties are removed entirely.
+    ///
+    /// Same-document links (`href="#foo"`) are kept and rewritten to point to the prefixed target
+    pub id_prefix: Option<&'a str>,
+
+    /// Tags you want to keep even if they end up empty (only used if `remove_empty_tags` is set)
+    pub keep_empty_tags: HashSet<&'a str>,
+
+    /// Wrap bare URLs and email addresses in text into links
+    ///
+    /// Only links with an allowed URL scheme are created. The attributes set on `a` tags via `set_tag_attributes` are applied to them as well.
+    /// Text inside of `a`, `code`, and `pre` tags is never linkified
+    pub linkify: bool,
+
+    /// Maximum number of consecutive `br` tags you want to keep
+    ///
+    /// Tags are consecutive if there is only whitespace between them. Any further `br` tags are removed
+    pub max_line_breaks: Option<usize>,
+
     /// Memory settings for the underlying HTML transformer
     pub memory_settings: MemorySettings,
 
+    /// Whitespace-separated tokens you want to merge into attributes on a per-tag basis (for example, `class` or `rel`)
+    ///
+    /// Existing tokens are kept, duplicates are dropped. Applied after `default_tag_attributes`
+    pub merge_tag_attributes: HashMap<&'a str, HashMap<&'a str, &'a str>>,
+
+    /// Renumber the headings so they never skip a level
+    ///
+    /// The first heading is at most at level `heading_offset + 1`, every following heading at most one level below the previous one
+    pub normalize_heading_levels: bool,
+
     /// Instead of removing tags (and potentially their content), escape the HTML instead and output them as raw text
     pub preserve_escaped: bool,
 
+    /// Tags you want to rename, mapped to their new name (for example, `b` to `strong`)
+    ///
+    /// Tags are renamed before they are checked against the allow-lists, meaning the allow-lists apply to the new name.
+    /// `remove_content_tags` still applies to the original name
+    pub rename_tags: HashMap<&'a str, &'a str>,
+
     /// Tags of which you want to remove the tag *and* the content of
     ///
     /// By default `bubble-bath` preserves the content of tags
@@ -110,54 +723,411 @@ pub struct BubbleBath<'a> {
     /// **Note**: Remember to put `<script>` and `<style>` tags in here (unless you 100% know what you are doing) since they are really damn evil!
     pub remove_content_tags: HashSet<&'a str>,
 
-    /// Attributes you want to set on a per-tag basis
-    pub set_tag_attributes: HashMap<&'a str, HashMap<&'a str, &'a str>>,
-}
+    /// Remove elements that end up empty after sanitization (for example, `<p></p>` and `<span> </span>`)
+    ///
+    /// Elements only containing whitespace count as empty, and so do elements only containing empty elements.
+    /// The whitespace is kept. Tags in `keep_empty_tags` and elements in foreign content are never removed
+    pub remove_empty_tags: bool,
+
+    /// Instead of removing `<style>` tags, sanitize their content using the [`css_sanitizer`](Self::css_sanitizer)
+    ///
+    /// Takes precedence over `remove_content_tags`. Has no effect unless a CSS sanitizer is set
+    pub sanitize_style_tags: bool,
+
+    /// Policy for inline SVG
+    ///
+    /// If set, elements in the SVG namespace are checked against this policy instead of the other allow-lists.
+    /// Otherwise they are checked against `allowed_foreign_tags`
+    pub svg_policy: Option<SvgPolicy<'a>>,
+
+    /// Attributes you want to set on a per-tag basis
+    ///
+    /// Existing values are overwritten. Check `default_tag_attributes` and `merge_tag_attributes` for the alternatives
+    pub set_tag_attributes: HashMap<&'a str, HashMap<&'a str, &'a str>>,
+
+    /// Class to scope all selectors of sanitized `<style>` tags under
+    ///
+    /// Wrap the output in an element with this class, so the styles can't leak into the rest of your page
+    pub style_scope: Option<&'a str>,
+
+    /// Hook you want to run on all text nodes
+    ///
+    /// The text is escaped after the hook ran. Text inside of removed content is not passed to the hook
+    pub text_hook: Option<TextHook<'a>>,
+
+    /// Turn tokens (for example, mentions and hashtags) in text into links
+    ///
+    /// The same rules as for `linkify` apply. The recognized tokens are returned by [`Self::clean_with_tokens`]
+    pub token_links: Option<TokenLinks<'a>>,
+
+    /// Repair the structure of tables (for example, cells outside of rows)
+    ///
+    /// Check [`TableRepair`] for details
+    pub table_repair: Option<TableRepair>,
+
+    /// Filter for Unicode characters in text and attribute values (for example, bidirectional overrides)
+    pub unicode_filter: Option<UnicodeFilter>,
+
+    /// Sanitize the output again and check whether it changed, guarding against mutation XSS
+    ///
+    /// If the output changes, a browser might parse it differently than we did. This doubles the cost of sanitization
+    /// and only applies to [`Self::clean`] and [`Self::clean_with_tokens`], since the streaming API can't buffer its output.
+    ///
+    /// The second pass skips the transforms that would apply again to their own output: shifting and normalizing headings,
+    /// linkification, token links, the text hook and element hooks
+    pub verification: Option<Verification>,
+}
+
+/// What to do if the output changes when sanitized again
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verification {
+    /// Escape the entire input and output it as text
+    Escape,
+
+    /// Return [`Error::Mutation`]
+    Reject,
+}
+
+impl BubbleBath<'_> {
+    /// Check whether the attribute is allowed on the tag in the namespace
+    #[inline]
+    fn is_attribute_allowed(
+        &self,
+        namespace: Namespace,
+        tag_name: &str,
+        attribute_name: &str,
+    ) -> bool {
+        if attribute_allowed(&self.allowed_generic_attributes, attribute_name) {
+            return true;
+        }
+
+        if attribute_name == "style" && self.css_sanitizer.is_some() {
+            return true;
+        }
+
+        if namespace != Namespace::Html {
+            return self
+                .allowed_foreign_tag_attributes
+                .get(&namespace)
+                .and_then(|tag_attributes| tag_attributes.get(tag_name))
+                .is_some_and(|allowed_attributes| {
+                    attribute_allowed(allowed_attributes, attribute_name)
+                });
+        }
+
+        if tag_name == "iframe"
+            && self.embed_policy.is_some()
+            && embed::EMBED_ATTRIBUTES.contains(&attribute_name)
+        {
+            return true;
+        }
+
+        if tag_name == "input"
+            && self.form_policy.is_some()
+            && forms::INPUT_ATTRIBUTES.contains(&attribute_name)
+        {
+            return true;
+        }
+
+        self.allowed_tag_attributes
+            .get(tag_name)
+            .is_some_and(|allowed_attributes| attribute_allowed(allowed_attributes, attribute_name))
+    }
+
+    #[inline]
+    fn clean_attributes(
+        &self,
+        element: &mut Element<'_, '_>,
+        namespace: Namespace,
+        tag_name: &str,
+    ) {
+        let mut remove_attributes = Vec::with_capacity(element.attributes().len());
+        for attribute in element.attributes() {
+            let attribute_name = attribute.name();
+
+            if !self.is_attribute_allowed(namespace, tag_name, &attribute_name) {
+                remove_attributes.push(attribute_name);
+            }
+        }
+
+        for attribute_name in remove_attributes {
+            element.remove_attribute(&attribute_name);
+        }
+    }
+
+    #[inline]
+    fn filter_attributes(&self, element: &mut Element<'_, '_>) -> HandlerResult {
+        let Some(unicode_filter) = &self.unicode_filter else {
+            return Ok(());
+        };
+
+        let mut filtered_attributes = Vec::new();
+        for attribute in element.attributes() {
+            let value = attribute.value();
+            if let Cow::Owned(filtered) = unicode_filter.filter(&entities::decode(&value)) {
+                filtered_attributes.push((attribute.name(), filtered));
+            }
+        }
+
+        for (name, value) in filtered_attributes {
+            element.set_attribute(&name, &value.replace('&', "&amp;"))?;
+        }
+
+        Ok(())
+    }
+
+    // The namespace is only needed to check the flag of the host policy
+    #[cfg_attr(not(feature = "idn"), allow(unused_variables))]
+    #[inline]
+    fn clean_link(
+        &self,
+        element: &mut Element<'_, '_>,
+        namespace: Namespace,
+        tag_name: &str,
+        attribute_name: &str,
+    ) -> HandlerResult {
+        let Some(raw_url) = element.get_attribute(attribute_name) else {
+            return Ok(());
+        };
+
+        if attribute_name == "srcset" {
+            let srcset = entities::decode(&raw_url);
+            let allowed = srcset_urls(&srcset).is_some_and(|urls| {
+                urls.iter().all(|url| {
+                    url_scheme(url).is_some_and(|scheme| self.allowed_url_schemes.contains(scheme))
+                })
+            });
+
+            if !allowed {
+                element.remove_attribute(attribute_name);
+            }
+
+            return Ok(());
+        }
+
+        // Same-document links are only kept if they can point to a prefixed ID
+        if let (Some(prefix), Some(fragment)) = (self.id_prefix, raw_url.strip_prefix('#')) {
+            if attribute_name == "href" {
+                if !fragment.is_empty() && !fragment.starts_with(prefix) {
+                    element.set_attribute(attribute_name, &format!("#{prefix}{fragment}"))?;
+                }
+
+                return Ok(());
+            }
+        }
+
+        let Some(scheme) = url_scheme(&raw_url) else {
+            element.remove_attribute(attribute_name);
+            return Ok(());
+        };
+
+        if !self.allowed_url_schemes.contains(scheme) {
+            element.remove_attribute(attribute_name);
+            return Ok(());
+        }
+
+        if let (Some(embed_policy), "iframe", "src") =
+            (&self.embed_policy, tag_name, attribute_name)
+        {
+            if !embed_policy.is_allowed_source(&entities::decode(&raw_url)) {
+                element.remove_attribute(attribute_name);
+                return Ok(());
+            }
+        }
+
+        #[cfg(feature = "idn")]
+        if let Some(host_policy) = &self.host_policy {
+            self.clean_host(
+                element,
+                namespace,
+                tag_name,
+                attribute_name,
+                &raw_url,
+                host_policy,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "idn")]
+    #[inline]
+    fn clean_host(
+        &self,
+        element: &mut Element<'_, '_>,
+        namespace: Namespace,
+        tag_name: &str,
+        attribute_name: &str,
+        raw_url: &str,
+        host_policy: &HostPolicy<'_>,
+    ) -> HandlerResult {
+        let url = entities::decode(raw_url);
+        let Some(range) = links::host_range(&url) else {
+            return Ok(());
+        };
+
+        // IP literals don't have any labels we could check
+        let host = &url[range.clone()];
+        if host.starts_with('[') {
+            return Ok(());
+        }
+
+        let checked = idn::check_host(host);
+        if checked.suspicious {
+            match host_policy.suspicious_hosts {
+                // The flag is only set if the allow-lists allow it on the element
+                SuspiciousHosts::Flag { name, value } => {
+                    if self.is_attribute_allowed(namespace, tag_name, name) {
+                        element.set_attribute(name, value)?;
+                    }
+                }
+                SuspiciousHosts::Reject => {
+                    element.remove_attribute(attribute_name);
+                    return Ok(());
+                }
+            }
+        }
+
+        if let Some(ascii) = checked.ascii.filter(|ascii| ascii != host) {
+            let url = format!("{}{ascii}{}", &url[..range.start], &url[range.end..]);
+            element.set_attribute(attribute_name, &url.replace('&', "&amp;"))?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn set_attributes<A>(&self, attributes: &mut A, tag_name: &str) -> HandlerResult
+    where
+        A: Attributes,
+    {
+        if let Some(set_attributes) = self.set_tag_attributes.get(tag_name) {
+            for (name, value) in sorted_attributes(set_attributes) {
+                // The `rel` tokens of links are up to the external link handling
+                if tag_name == "a" && name == "rel" && self.external_links.is_some() {
+                    continue;
+                }
+
+                attributes.set(name, value)?;
+            }
+        }
+
+        if let Some(default_attributes) = self.default_tag_attributes.get(tag_name) {
+            for (name, value) in sorted_attributes(default_attributes) {
+                let value = attributes.get(name).unwrap_or_else(|| value.to_string());
+                attributes.set(name, &value)?;
+            }
+        }
+
+        if let Some(merge_attributes) = self.merge_tag_attributes.get(tag_name) {
+            for (name, value) in sorted_attributes(merge_attributes) {
+                let merged = merge_tokens(attributes.get(name).as_deref(), [value]);
+                attributes.set(name, &merged)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn mark_external_link<A>(&self, attributes: &mut A) -> HandlerResult
+    where
+        A: Attributes,
+    {
+        let Some(external_links) = &self.external_links else {
+            return Ok(());
+        };
+
+        let Some(href) = attributes.get("href") else {
+            return Ok(());
+        };
+
+        if !external_links.is_external(&entities::decode(&href)) {
+            return Ok(());
+        }
+
+        let rel = external_links.merge_rel(attributes.get("rel").as_deref());
+        if !rel.is_empty() {
+            attributes.set("rel", &rel)?;
+        }
+
+        if external_links.target_blank {
+            attributes.set("target", "_blank")?;
+        }
+
+        Ok(())
+    }
+
+    /// Enforce the embed policy on an `iframe`, removing it if its source isn't allowed
+    #[inline]
+    fn clean_embed(&self, element: &mut Element<'_, '_>) -> HandlerResult {
+        let Some(embed_policy) = &self.embed_policy else {
+            return Ok(());
+        };
+
+        self.clean_link(element, Namespace::Html, "iframe", "src")?;
+        if !element.has_attribute("src") {
+            element.remove();
+            return Ok(());
+        }
+
+        element.set_attribute("sandbox", &embed_policy.sandbox.join(" "))?;
+        element.set_attribute("referrerpolicy", embed_policy.referrer_policy)?;
 
-impl BubbleBath<'_> {
-    #[inline]
-    fn clean_attributes(&self, element: &mut Element<'_, '_>, tag_name: &str) {
-        let allowed_attributes = self.allowed_tag_attributes.get(tag_name);
+        if let Some(allow) = element.get_attribute("allow") {
+            let allow = embed_policy.filter_permissions(&entities::decode(&allow));
+            if allow.is_empty() {
+                element.remove_attribute("allow");
+            } else {
+                element.set_attribute("allow", &allow)?;
+            }
+        }
 
-        let mut remove_attributes = Vec::with_capacity(element.attributes().len());
-        for attribute in element.attributes() {
-            let attribute_name = attribute.name();
+        Ok(())
+    }
+
+    #[inline]
+    fn prefix_ids(&self, element: &mut Element<'_, '_>) -> HandlerResult {
+        let Some(prefix) = self.id_prefix else {
+            return Ok(());
+        };
 
-            if self
-                .allowed_generic_attributes
-                .contains(attribute_name.as_str())
-            {
+        for attribute_name in ["id", "name"] {
+            let Some(value) = element.get_attribute(attribute_name) else {
                 continue;
-            }
+            };
 
-            if let Some(allowed_attributes) = allowed_attributes {
-                if allowed_attributes.contains(attribute_name.as_str()) {
-                    continue;
-                }
+            // Don't prefix values twice, so sanitizing our own output doesn't change it
+            let unprefixed = value.strip_prefix(prefix).unwrap_or(&value);
+            if unprefixed.is_empty() || CLOBBERABLE_NAMES.contains(&unprefixed) {
+                element.remove_attribute(attribute_name);
+                continue;
             }
 
-            remove_attributes.push(attribute_name);
+            element.set_attribute(attribute_name, &format!("{prefix}{unprefixed}"))?;
         }
 
-        for attribute_name in remove_attributes {
-            element.remove_attribute(&attribute_name);
-        }
+        Ok(())
     }
 
     #[inline]
-    fn clean_link(&self, element: &mut Element<'_, '_>, attribute_name: &str) {
-        let Some(raw_url) = element.get_attribute(attribute_name) else {
-            return;
+    fn clean_style(&self, element: &mut Element<'_, '_>) -> HandlerResult {
+        let Some(css_sanitizer) = &self.css_sanitizer else {
+            return Ok(());
         };
 
-        let Some((scheme, _rest)) = raw_url.split_once("://") else {
-            element.remove_attribute(attribute_name);
-            return;
+        let Some(raw_style) = element.get_attribute("style") else {
+            return Ok(());
         };
 
-        if !self.allowed_url_schemes.contains(scheme) {
-            element.remove_attribute(attribute_name);
+        let style = css_sanitizer.sanitize_declarations(&raw_style, &self.allowed_url_schemes);
+        if style.is_empty() {
+            element.remove_attribute("style");
+        } else {
+            element.set_attribute("style", &style)?;
         }
+
+        Ok(())
     }
 
     #[inline]
@@ -178,66 +1148,304 @@ impl BubbleBath<'_> {
                 formatted.push('>');
             }
 
-            start_tag.replace(&formatted, ContentType::Text);
+            // Escape it like any other text, so sanitizing the output again doesn't change it
+            start_tag.replace(&clean_text(&formatted), ContentType::Html);
 
             if let Some(handlers) = element.end_tag_handlers() {
                 handlers.push(Box::new(move |end_tag| {
                     let tag_name = end_tag.name();
-                    let content = format!("</{tag_name}>");
-                    end_tag.replace(&content, ContentType::Text);
+                    let content = clean_text(&format!("</{tag_name}>"));
+                    end_tag.replace(&content, ContentType::Html);
 
                     Ok(())
                 }));
             }
         } else {
-            element.remove_and_keep_content();
+            unwrap_element(element);
+        }
+    }
+
+    #[inline]
+    fn shift_heading(&self, tag_name: String, state: &State) -> String {
+        let Some(level) = heading_level(&tag_name) else {
+            return tag_name;
+        };
+
+        if state.verifying || (self.heading_offset == 0 && !self.normalize_heading_levels) {
+            return tag_name;
+        }
+
+        let mut level = level.saturating_add(self.heading_offset).min(6);
+        if self.normalize_heading_levels {
+            let max_level = state.last_heading_level.get().max(self.heading_offset) + 1;
+            level = level.min(max_level);
+            state.last_heading_level.set(level);
+        }
+
+        format!("h{level}")
+    }
+
+    /// Rename the element by replacing its start tag
+    ///
+    /// We can't use `Element::set_tag_name` since `lol_html` would also rename the end tag of the ancestor
+    /// if the element is closed implicitly (which closes our element through the end tag handler of [`State::enter_element`])
+    #[inline]
+    fn rename_element(element: &mut Element<'_, '_>, source_name: String, tag_name: &str) {
+        let start_tag = start_tag(element, tag_name);
+        element.start_tag().replace(&start_tag, ContentType::Html);
+
+        if let Some(end_tag_handlers) = element.end_tag_handlers() {
+            let tag_name = tag_name.to_string();
+            end_tag_handlers.push(Box::new(move |end_tag| {
+                if end_tag.name() == source_name {
+                    end_tag.set_name_str(tag_name);
+                }
+
+                Ok(())
+            }));
+        }
+    }
+
+    #[inline]
+    fn style_tag_handler(element: &mut Element<'_, '_>, state: &Rc<State>) {
+        let attribute_names: Vec<_> = element.attributes().iter().map(Attribute::name).collect();
+
+        for attribute_name in attribute_names {
+            element.remove_attribute(&attribute_name);
+        }
+
+        *state.style_sheet.borrow_mut() = Some(String::new());
+
+        if let Some(end_tag_handlers) = element.end_tag_handlers() {
+            let state = Rc::clone(state);
+            end_tag_handlers.push(Box::new(move |_end_tag| {
+                *state.style_sheet.borrow_mut() = None;
+                Ok(())
+            }));
         }
+
+        state.enter_element(element, "style".into(), Namespace::Html);
+        state.emit_start_tag(element, "style", false);
     }
 
     #[inline]
-    fn element_handler(
+    fn svg_element_handler(
         &self,
+        svg_policy: &SvgPolicy<'_>,
         element: &mut Element<'_, '_>,
-        unclosed_tags: Rc<RefCell<Slab<String>>>,
+        state: &Rc<State>,
     ) -> HandlerResult {
         let tag_name = element.tag_name();
 
-        if self.remove_content_tags.contains(tag_name.as_str()) {
+        if svg_policy.allowed_tags.contains(tag_name.as_str())
+            && !namespace::RAW_TEXT_ELEMENTS.contains(&tag_name.as_str())
+        {
+            self.filter_attributes(element)?;
+            svg_policy.clean_element(element, &tag_name, self.id_prefix)?;
+        } else {
+            element.remove();
+        }
+
+        if element.removed() {
+            state.enter_removed_content(element);
+            return Ok(());
+        }
+
+        state.enter_element(element, tag_name.clone(), Namespace::Svg);
+        state.emit_start_tag(element, &tag_name, false);
+
+        Ok(())
+    }
+
+    /// Check whether the element is allowed in the foreign namespace
+    #[inline]
+    fn is_foreign_tag_allowed(&self, namespace: Namespace, tag_name: &str) -> bool {
+        if namespace::RAW_TEXT_ELEMENTS.contains(&tag_name)
+            || (namespace == Namespace::MathMl && !namespace::MATHML_ELEMENTS.contains(&tag_name))
+        {
+            return false;
+        }
+
+        self.allowed_foreign_tags
+            .get(&namespace)
+            .is_some_and(|tags| tags.contains(tag_name))
+    }
+
+    /// Name the element in the HTML namespace is output as, or `None` if it was removed
+    #[inline]
+    fn html_tag_name(
+        &self,
+        element: &mut Element<'_, '_>,
+        source_name: &str,
+        state: &State,
+    ) -> Option<String> {
+        let tag_name = self
+            .rename_tags
+            .get(source_name)
+            .map_or_else(|| source_name.to_string(), |name| (*name).to_string());
+        let tag_name = self.shift_heading(tag_name, state);
+
+        let embed = tag_name == "iframe" && self.embed_policy.is_some();
+        let form_control = tag_name == "input" && self.form_policy.is_some();
+        let form = tag_name == "form" && self.form_policy.is_some();
+        if (!self.allowed_tags.contains(tag_name.as_str()) && !embed && !form_control) || form {
+            self.delete_element(element, source_name);
+            return None;
+        }
+
+        // Table elements outside of tables are removed while keeping their content
+        if self.table_repair.is_some()
+            && state.removed_content.get() == 0
+            && !state.repair_table(element, &tag_name)
+        {
+            self.delete_element(element, source_name);
+            return None;
+        }
+
+        if self.content_model.is_some_and(|content_model| {
+            state.removed_content.get() == 0
+                && !state.enforce_content_model(element, &tag_name, content_model)
+        }) {
+            self.delete_element(element, source_name);
+            return None;
+        }
+
+        if tag_name == "br"
+            && self
+                .max_line_breaks
+                .is_some_and(|max_line_breaks| state.line_breaks.get() >= max_line_breaks)
+        {
             element.remove();
+            return None;
+        }
+
+        Some(tag_name)
+    }
+
+    #[inline]
+    fn element_handler(&self, element: &mut Element<'_, '_>, state: &Rc<State>) -> HandlerResult {
+        let source_name = element.tag_name();
+        let namespace = state.child_namespace(&source_name);
+
+        // The content of `<style>` tags in foreign content is parsed as markup, so we can't sanitize it as a style sheet
+        if source_name == "style"
+            && namespace == Namespace::Html
+            && self.sanitize_style_tags
+            && self.css_sanitizer.is_some()
+        {
+            Self::style_tag_handler(element, state);
             return Ok(());
         }
 
-        if !self.allowed_tags.contains(tag_name.as_str()) {
-            self.delete_element(element, &tag_name);
+        if self.remove_content_tags.contains(source_name.as_str()) {
+            element.remove();
+            state.enter_removed_content(element);
             return Ok(());
         }
 
-        self.clean_attributes(element, &tag_name);
+        let tag_name = if namespace == Namespace::Html {
+            let Some(tag_name) = self.html_tag_name(element, &source_name, state) else {
+                return Ok(());
+            };
+
+            tag_name
+        } else {
+            if let (Namespace::Svg, Some(svg_policy)) = (namespace, &self.svg_policy) {
+                return self.svg_element_handler(svg_policy, element, state);
+            }
+
+            if !self.is_foreign_tag_allowed(namespace, &source_name) {
+                // Only the element starting the foreign content can be removed without moving its content into a different namespace
+                if state.namespace() == namespace {
+                    element.remove();
+                    state.enter_removed_content(element);
+                } else {
+                    self.delete_element(element, &source_name);
+                }
+
+                return Ok(());
+            }
+
+            source_name.clone()
+        };
+
+        self.clean_attributes(element, namespace, &tag_name);
+        self.filter_attributes(element)?;
 
-        if let Some(set_attributes) = self.set_tag_attributes.get(tag_name.as_str()) {
-            for (name, value) in set_attributes {
-                element.set_attribute(name, value)?;
+        let hooks = self.element_hooks.get(tag_name.as_str());
+        if let Some(hooks) = hooks.filter(|_| !state.verifying) {
+            let mut element_view = ElementView::new(element, self, namespace, &tag_name);
+            for hook in hooks {
+                hook(&mut element_view)?;
+            }
+
+            if element_view.is_content_removed() {
+                state.enter_removed_content(element);
+                return Ok(());
+            }
+
+            if element_view.is_removed() {
+                return Ok(());
             }
         }
 
+        self.clean_style(element)?;
+        self.prefix_ids(element)?;
+
+        self.set_attributes(element, &tag_name)?;
+
         if let Some(attributes) = self.clean_url_attributes.get(tag_name.as_str()) {
             for name in attributes {
-                self.clean_link(element, name);
+                self.clean_link(element, namespace, &tag_name, name)?;
             }
         }
 
-        // Manually balance the tags if they aren't self-closing
-        if !element.is_self_closing() {
-            let unclosed_tag_idx = {
-                let mut unclosed_tags = unclosed_tags.borrow_mut();
-                unclosed_tags.insert(tag_name)
-            };
+        if tag_name == "a" {
+            self.mark_external_link(element)?;
+        }
 
-            if let Some(end_tag_handlers) = element.end_tag_handlers() {
-                end_tag_handlers.push(Box::new(move |_end_tag| {
-                    unclosed_tags.borrow_mut().remove(unclosed_tag_idx);
-                    Ok(())
-                }));
+        if tag_name == "iframe" {
+            self.clean_embed(element)?;
+
+            if element.removed() {
+                state.enter_removed_content(element);
+                return Ok(());
+            }
+        }
+
+        if let Some(form_policy) = &self.form_policy {
+            form_policy.clean_element(element, &tag_name)?;
+
+            if element.removed() {
+                return Ok(());
+            }
+        }
+
+        if let Some(table_repair) = &self.table_repair {
+            if matches!(tag_name.as_str(), "td" | "th") {
+                table_repair.clamp_spans(element)?;
+            }
+        }
+
+        if tag_name != source_name {
+            Self::rename_element(element, source_name, &tag_name);
+        }
+
+        state.enter_element(element, tag_name.clone(), namespace);
+
+        let defer = self.remove_empty_tags
+            && namespace == Namespace::Html
+            && !self.keep_empty_tags.contains(tag_name.as_str());
+        state.emit_start_tag(element, &tag_name, defer);
+
+        #[cfg(feature = "idn")]
+        if tag_name == "a"
+            && self
+                .host_policy
+                .is_some_and(|policy| policy.rewrite_link_text)
+        {
+            if let Some(href) = element.get_attribute("href") {
+                state.enter_link(element, entities::decode(&href).into_owned());
             }
         }
 
@@ -276,9 +1484,228 @@ impl BubbleBath<'_> {
     }
 
     #[inline]
-    fn text_handler(chunk: &mut TextChunk<'_>, opening_tags: &RefCell<usize>) {
+    fn text_handler(
+        &self,
+        chunk: &mut TextChunk<'_>,
+        opening_tags: &RefCell<usize>,
+        state: &State,
+    ) {
         Self::subtract_opening_tags(&mut opening_tags.borrow_mut(), chunk.as_str());
-        *chunk.as_mut_str() = clean_text(chunk.as_str());
+
+        // Style sheets have to be buffered in their entirety before we can sanitize them
+        if let Some(buffer) = state.style_sheet.borrow_mut().as_mut() {
+            buffer.push_str(chunk.as_str());
+
+            if chunk.last_in_text_node() {
+                let css_sanitizer = self
+                    .css_sanitizer
+                    .as_ref()
+                    .expect("style sheets are only buffered if a CSS sanitizer is set");
+
+                let sanitized = css_sanitizer.sanitize_stylesheet(
+                    buffer,
+                    self.style_scope,
+                    &self.allowed_url_schemes,
+                );
+                buffer.clear();
+
+                chunk.replace(&sanitized, ContentType::Html);
+            } else {
+                chunk.remove();
+            }
+
+            return;
+        }
+
+        // Text that isn't whitespace is content of the elements we are inside of
+        if state.removed_content.get() == 0
+            && !chunk
+                .as_str()
+                .bytes()
+                .all(|byte| byte.is_ascii_whitespace())
+        {
+            let start_tags = state.emit_content(false);
+            if !start_tags.is_empty() {
+                chunk.before(&start_tags, ContentType::Html);
+            }
+        }
+
+        let text_type = chunk.text_type();
+        let collapse_whitespace = self.collapse_whitespace
+            && text_type == TextType::Data
+            && !state
+                .elements
+                .borrow()
+                .iter()
+                .any(|tag_name| matches!(tag_name.as_str(), "code" | "pre"));
+        let linkify = (self.linkify || self.token_links.is_some())
+            && !state.verifying
+            && text_type == TextType::Data
+            && !state
+                .elements
+                .borrow()
+                .iter()
+                .any(|tag_name| matches!(tag_name.as_str(), "a" | "code" | "pre"));
+
+        // Only text nodes directly inside of the link are compared against its URL
+        let link_url = state
+            .link
+            .borrow()
+            .as_ref()
+            .filter(|(depth, _url)| *depth == state.elements.borrow().len())
+            .map(|(_depth, url)| url.clone());
+
+        let text_hook = self.text_hook.as_ref().filter(|_| !state.verifying);
+        let buffer =
+            text_hook.is_some() || self.unicode_filter.is_some() || linkify || link_url.is_some();
+        if buffer && state.removed_content.get() == 0 {
+            Self::buffer_text(chunk, state, |text| {
+                let mut text = match text_type {
+                    TextType::Data | TextType::RCData => entities::decode_text(text),
+                    _ => Cow::Borrowed(text),
+                };
+
+                if let Some(unicode_filter) = &self.unicode_filter {
+                    if let Cow::Owned(filtered) = unicode_filter.filter(&text) {
+                        text = Cow::Owned(filtered);
+                    }
+                }
+
+                if collapse_whitespace {
+                    text = Cow::Owned(state.collapse_whitespace(&text).into_owned());
+                }
+
+                if let Some(text_hook) = text_hook {
+                    text = Cow::Owned(text_hook(&text, &state.elements.borrow()));
+                }
+
+                #[cfg(feature = "idn")]
+                if let Some(url) = link_url {
+                    if idn::is_misleading(&text, &url) {
+                        text = Cow::Owned(url);
+                    }
+                }
+
+                if linkify {
+                    self.linkify(&text, state)
+                } else {
+                    clean_text(&text)
+                }
+            });
+
+            return;
+        }
+
+        // Decode the character references first, so already escaped text isn't escaped twice
+        let cleaned = {
+            let mut text = state.decode_chunk(chunk);
+
+            if collapse_whitespace {
+                text = Cow::Owned(state.collapse_whitespace(&text).into_owned());
+            }
+
+            clean_text(&text)
+        };
+        *chunk.as_mut_str() = cleaned;
+    }
+
+    /// Write a link into the accumulator
+    #[inline]
+    fn write_link(&self, acc: &mut String, href: &str, content: &str) {
+        let mut attributes = vec![("href".to_string(), href.to_string())];
+
+        // Setting attributes on a vector can't fail
+        let _ = self.set_attributes(&mut attributes, "a");
+        let _ = self.mark_external_link(&mut attributes);
+
+        acc.push_str("<a");
+        for (name, value) in attributes {
+            let _ = write!(acc, r#" {name}="{}""#, escape_attribute(&value));
+        }
+        let _ = write!(acc, ">{}</a>", clean_text(content));
+    }
+
+    /// Turn all the URLs, email addresses and tokens in the text into links and escape the rest
+    #[inline]
+    fn linkify(&self, text: &str, state: &State) -> String {
+        let mut acc = String::with_capacity(text.len());
+        let mut rest = text;
+
+        loop {
+            let link = self.linkify.then(|| linkify::find(rest)).flatten();
+            let token = self
+                .token_links
+                .as_ref()
+                .and_then(|token_links| (token_links.recognizer)(rest))
+                .filter(|(range, _token)| {
+                    !range.is_empty() && link.as_ref().map_or(true, |link| range.start < link.start)
+                });
+
+            let end = match (link, token) {
+                (_, Some((range, token))) => {
+                    let token_links = self
+                        .token_links
+                        .as_ref()
+                        .expect("tokens are only recognized if token links are configured");
+
+                    acc.push_str(&clean_text(&rest[..range.start]));
+                    self.write_link(&mut acc, &token_links.url(&token), &rest[range.clone()]);
+
+                    let mut tokens = state.tokens.borrow_mut();
+                    if !tokens.contains(&token) {
+                        tokens.push(token);
+                    }
+
+                    range.end
+                }
+                (Some(link), None) => {
+                    let content = &rest[link.start..link.end];
+                    let href = match link.kind {
+                        LinkKind::Email => Cow::Owned(format!("mailto:{content}")),
+                        LinkKind::Url => Cow::Borrowed(content),
+                    };
+                    let scheme = match link.kind {
+                        LinkKind::Email => Some("mailto"),
+                        LinkKind::Url => url_scheme(content),
+                    };
+
+                    acc.push_str(&clean_text(&rest[..link.start]));
+
+                    if scheme.is_some_and(|scheme| self.allowed_url_schemes.contains(scheme)) {
+                        self.write_link(&mut acc, &href, content);
+                    } else {
+                        acc.push_str(&clean_text(content));
+                    }
+
+                    link.end
+                }
+                (None, None) => break,
+            };
+
+            rest = &rest[end..];
+        }
+
+        acc.push_str(&clean_text(rest));
+        acc
+    }
+
+    /// Buffer the chunks of a text node and replace the whole node with the output of the function
+    #[inline]
+    fn buffer_text<F>(chunk: &mut TextChunk<'_>, state: &State, func: F)
+    where
+        F: FnOnce(&str) -> String,
+    {
+        let mut buffer = state.text.borrow_mut();
+        buffer.push_str(chunk.as_str());
+
+        if chunk.last_in_text_node() {
+            let output = func(&buffer);
+            buffer.clear();
+
+            chunk.replace(&output, ContentType::Html);
+        } else {
+            chunk.remove();
+        }
     }
 
     /// Clean HTML in a streaming fashion
@@ -294,24 +1721,66 @@ impl BubbleBath<'_> {
         I: Iterator<Item = &'a [u8]>,
         S: FnMut(&[u8]),
     {
-        let unclosed_tags = Rc::new(RefCell::new(Slab::new()));
+        self.clean_streaming_with_tokens(input, sink)?;
+        Ok(())
+    }
+
+    /// Clean HTML in a streaming fashion and return the tokens that were turned into links
+    ///
+    /// The tokens are returned in order of their first appearance and without duplicates.
+    /// Check [`Self::token_links`] for the configuration
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::clean_streaming`]
+    #[inline]
+    pub fn clean_streaming_with_tokens<'a, I, S>(
+        &self,
+        input: I,
+        sink: S,
+    ) -> Result<Vec<Token>, Error>
+    where
+        I: Iterator<Item = &'a [u8]>,
+        S: FnMut(&[u8]),
+    {
+        self.rewrite(input, sink, false)
+    }
+
+    /// Clean HTML in a streaming fashion, skipping the transforms that aren't idempotent if we are verifying our own output
+    #[inline]
+    fn rewrite<'a, I, S>(&self, input: I, sink: S, verifying: bool) -> Result<Vec<Token>, Error>
+    where
+        I: Iterator<Item = &'a [u8]>,
+        S: FnMut(&[u8]),
+    {
+        // Don't ask me why we need this. This is dumb and I don't like it.
+        // It's required so the compiler recognizes that our closure, indeed, implements the handler trait.
+        #[inline]
+        fn bounds_assertion<T>(uwu: T) -> T
+        where
+            T: FnMut(&mut Element<'_, '_>) -> HandlerResult,
+        {
+            uwu
+        }
+
         let opening_tags = RefCell::new(0);
+        let state = Rc::new(State {
+            verifying,
+            ..State::default()
+        });
 
         let comment_handler = |comment: &mut Comment<'_>| {
             Self::comment_handler(comment, &opening_tags);
             Ok(())
         };
         let document_end_handler = |document_end: &mut DocumentEnd<'_>| {
-            let unclosed_tags = unclosed_tags.borrow();
-            for (_key, content) in unclosed_tags.iter() {
-                let formatted = format!("</{content}>");
-                document_end.append(&formatted, ContentType::Html);
-            }
+            // Close the elements that are still open, since their end tags were omitted
+            document_end.append(&state.close_elements(0), ContentType::Html);
 
             Ok(())
         };
         let text_handler = |chunk: &mut TextChunk<'_>| {
-            Self::text_handler(chunk, &opening_tags);
+            self.text_handler(chunk, &opening_tags, &state);
             Ok(())
         };
 
@@ -320,20 +1789,10 @@ impl BubbleBath<'_> {
             .text(text_handler)
             .end(document_end_handler)];
 
-        // Don't ask me why we need this. This is dumb and I don't like it.
-        // It's required so the compiler recognizes that our closure, indeed, implements the handler trait.
-        #[inline(always)]
-        fn bounds_assertion<T>(uwu: T) -> T
-        where
-            T: FnMut(&mut Element<'_, '_>) -> HandlerResult,
-        {
-            uwu
-        }
-
         let element_content_handlers = vec![(
             Cow::Borrowed(&*SELECT_ALL),
             ElementContentHandlers::default().element(bounds_assertion(|element| {
-                self.element_handler(element, unclosed_tags.clone())
+                self.element_handler(element, &state)
             })),
         )];
 
@@ -359,12 +1818,12 @@ impl BubbleBath<'_> {
 
         let opening_tags = *opening_tags.borrow();
         for _ in 0..opening_tags {
-            rewriter.write(&[b'>'])?;
+            rewriter.write(b">")?;
         }
 
         rewriter.end()?;
 
-        Ok(())
+        Ok(state.tokens.take())
     }
 
     /// Clean the provided HTML content
@@ -376,10 +1835,58 @@ impl BubbleBath<'_> {
     /// Check [`Self::clean_streaming`] for additional errors
     #[inline]
     pub fn clean(&self, content: &str) -> Result<String, Error> {
+        self.clean_with_tokens(content)
+            .map(|(cleaned, _tokens)| cleaned)
+    }
+
+    /// Clean the provided HTML content and return the tokens that were turned into links
+    ///
+    /// Check [`Self::clean_streaming_with_tokens`] for details
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::clean`]
+    #[inline]
+    pub fn clean_with_tokens(&self, content: &str) -> Result<(String, Vec<Token>), Error> {
+        let (cleaned, tokens) = self.clean_once(content, false)?;
+        let Some(verification) = self.verification else {
+            return Ok((cleaned, tokens));
+        };
+
+        if self.clean_once(&cleaned, true)?.0 == cleaned {
+            return Ok((cleaned, tokens));
+        }
+
+        match verification {
+            Verification::Escape => Ok((clean_text(content), Vec::new())),
+            Verification::Reject => Err(Error::Mutation),
+        }
+    }
+
+    /// Check whether sanitizing the output again doesn't change it
+    ///
+    /// If the output changes, a browser might parse it differently than we did, which is the root of mutation XSS.
+    /// Useful for testing your policies against a corpus of inputs. Check [`Self::verification`] for doing this on every call,
+    /// and for the transforms the second pass skips
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::clean`]
+    #[inline]
+    pub fn is_idempotent(&self, content: &str) -> Result<bool, Error> {
+        let (cleaned, _tokens) = self.clean_once(content, false)?;
+        Ok(self.clean_once(&cleaned, true)?.0 == cleaned)
+    }
+
+    /// Clean the provided HTML content without verifying the output
+    #[inline]
+    fn clean_once(&self, content: &str, verifying: bool) -> Result<(String, Vec<Token>), Error> {
         let mut acc = Vec::with_capacity(content.len());
-        self.clean_streaming(iter::once(content.as_bytes()), |out| {
-            acc.extend_from_slice(out);
-        })?;
+        let tokens = self.rewrite(
+            iter::once(content.as_bytes()),
+            |out| acc.extend_from_slice(out),
+            verifying,
+        )?;
 
         // SAFETY: Since the input is a string slice, we can be confident that it is valid UTF-8.
         // We also buffered the entirety of the output into the accumulator.
@@ -390,7 +1897,7 @@ impl BubbleBath<'_> {
         // Meaning, since we have the entire output accumulated and the source encoding is valid UTF-8,
         // this byte vector is, indeed, valid UTF-8.
         #[allow(unsafe_code)]
-        Ok(unsafe { String::from_utf8_unchecked(acc) })
+        Ok((unsafe { String::from_utf8_unchecked(acc) }, tokens))
     }
 }
 
@@ -499,6 +2006,7 @@ impl Default for BubbleBath<'static> {
             "img" => hashset!["src"],
             "link" => hashset!["href"],
         ];
+        let keep_empty_tags = hashset!["br", "iframe", "img", "td", "th"];
         let remove_content_tags = hashset!["script", "style"];
         let set_tag_attributes = hashmap![
             "a" => hashmap![
@@ -507,15 +2015,44 @@ impl Default for BubbleBath<'static> {
         ];
 
         Self {
+            allowed_foreign_tags: HashMap::default(),
+            allowed_foreign_tag_attributes: HashMap::default(),
             allowed_tags,
             allowed_generic_attributes,
             allowed_tag_attributes,
             allowed_url_schemes,
             clean_url_attributes,
+            collapse_whitespace: false,
+            content_model: None,
+            css_sanitizer: None,
+            default_tag_attributes: HashMap::default(),
+            element_hooks: HashMap::default(),
+            embed_policy: None,
+            form_policy: None,
+            external_links: None,
+            heading_offset: 0,
+            #[cfg(feature = "idn")]
+            host_policy: None,
+            id_prefix: None,
+            keep_empty_tags,
+            linkify: false,
+            max_line_breaks: None,
             memory_settings: MemorySettings::default(),
+            merge_tag_attributes: HashMap::default(),
+            normalize_heading_levels: false,
             preserve_escaped: false,
             remove_content_tags,
+            remove_empty_tags: false,
+            rename_tags: HashMap::default(),
+            sanitize_style_tags: false,
             set_tag_attributes,
+            style_scope: None,
+            svg_policy: None,
+            text_hook: None,
+            token_links: None,
+            table_repair: None,
+            unicode_filter: None,
+            verification: None,
         }
     }
 }
@@ -0,0 +1,153 @@
+//! Detection of bare URLs and email addresses in text
+
+/// Kind of link we found
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LinkKind {
+    /// Email address (needs a `mailto:` prefix to be used as a link)
+    Email,
+
+    /// URL with a scheme
+    Url,
+}
+
+/// Link we found in a piece of text
+#[derive(Debug)]
+pub(crate) struct Link {
+    /// Byte offset of the start of the link
+    pub start: usize,
+
+    /// Byte offset of the end of the link
+    pub end: usize,
+
+    /// Kind of the link
+    pub kind: LinkKind,
+}
+
+#[inline]
+fn is_scheme_char(chr: u8) -> bool {
+    chr.is_ascii_alphanumeric() || matches!(chr, b'+' | b'-' | b'.')
+}
+
+#[inline]
+fn is_local_part_char(chr: u8) -> bool {
+    chr.is_ascii_alphanumeric() || matches!(chr, b'.' | b'_' | b'%' | b'+' | b'-')
+}
+
+#[inline]
+fn is_domain_char(chr: u8) -> bool {
+    chr.is_ascii_alphanumeric() || matches!(chr, b'.' | b'-')
+}
+
+/// Remove trailing punctuation that most likely belongs to the surrounding sentence
+#[inline]
+fn trim_url_end(url: &str) -> usize {
+    let mut end = url.len();
+
+    while let Some(last) = url[..end].chars().next_back() {
+        let trim = match last {
+            '.' | ',' | ':' | ';' | '!' | '?' | '\'' => true,
+            ')' => url[..end].matches('(').count() < url[..end].matches(')').count(),
+            ']' => url[..end].matches('[').count() < url[..end].matches(']').count(),
+            _ => false,
+        };
+
+        if !trim {
+            break;
+        }
+
+        end -= last.len_utf8();
+    }
+
+    end
+}
+
+/// Try to find a URL whose scheme separator (`://`) starts at the given offset
+#[inline]
+fn url_at(text: &str, separator: usize) -> Option<Link> {
+    let bytes = text.as_bytes();
+
+    let mut start = separator;
+    while start > 0 && is_scheme_char(bytes[start - 1]) {
+        start -= 1;
+    }
+
+    // Schemes have to start with a letter
+    while start < separator && !bytes[start].is_ascii_alphabetic() {
+        start += 1;
+    }
+
+    if start == separator {
+        return None;
+    }
+
+    let rest_start = separator + "://".len();
+    let rest = &text[rest_start..];
+    let rest_len = rest
+        .find(|chr: char| {
+            chr.is_whitespace() || chr.is_control() || matches!(chr, '<' | '>' | '"' | '`')
+        })
+        .unwrap_or(rest.len());
+
+    let end = rest_start + trim_url_end(&rest[..rest_len]);
+    if end == rest_start {
+        return None;
+    }
+
+    Some(Link {
+        start,
+        end,
+        kind: LinkKind::Url,
+    })
+}
+
+/// Try to find an email address whose `@` is at the given offset
+#[inline]
+fn email_at(text: &str, at: usize) -> Option<Link> {
+    let bytes = text.as_bytes();
+
+    let mut start = at;
+    while start > 0 && is_local_part_char(bytes[start - 1]) {
+        start -= 1;
+    }
+
+    while start < at && bytes[start] == b'.' {
+        start += 1;
+    }
+
+    let mut end = at + 1;
+    while end < bytes.len() && is_domain_char(bytes[end]) {
+        end += 1;
+    }
+
+    while end > at + 1 && matches!(bytes[end - 1], b'.' | b'-') {
+        end -= 1;
+    }
+
+    let domain = &text[at + 1..end];
+    let valid = start < at
+        && domain
+            .split('.')
+            .all(|label| !label.is_empty() && !label.starts_with('-'))
+        && domain.contains('.');
+
+    valid.then_some(Link {
+        start,
+        end,
+        kind: LinkKind::Email,
+    })
+}
+
+/// Find the first link in the text
+#[inline]
+pub(crate) fn find(text: &str) -> Option<Link> {
+    text.match_indices(['@', ':'])
+        .find_map(|(offset, matched)| {
+            if matched == "@" {
+                email_at(text, offset)
+            } else if text[offset..].starts_with("://") {
+                url_at(text, offset)
+            } else {
+                None
+            }
+        })
+}
@@ -0,0 +1,76 @@
+use ahash::HashSet;
+use std::ops::Range;
+
+/// Configuration for links pointing to external hosts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExternalLinks<'a> {
+    /// Hosts you consider internal (for example, `example.com`)
+    ///
+    /// Hosts are compared case-insensitively and without the port. Subdomains have to be listed separately.
+    /// Links without a host (for example, `mailto:` links) are always considered internal
+    pub internal_hosts: HashSet<&'a str>,
+
+    /// Tokens merged into the `rel` attribute of external links
+    pub rel: Vec<&'a str>,
+
+    /// Set `target="_blank"` on external links
+    ///
+    /// Make sure to keep `noopener` in the `rel` tokens when enabling this
+    pub target_blank: bool,
+}
+
+impl ExternalLinks<'_> {
+    /// Check whether the URL points to an external host
+    #[must_use]
+    pub fn is_external(&self, url: &str) -> bool {
+        let Some(range) = host_range(url) else {
+            return false;
+        };
+
+        let host = &url[range];
+        !host.is_empty()
+            && !self
+                .internal_hosts
+                .iter()
+                .any(|internal_host| internal_host.eq_ignore_ascii_case(host))
+    }
+
+    /// Merge the configured tokens into the existing value of a `rel` attribute
+    ///
+    /// Existing tokens are kept in their original order, duplicates are dropped
+    #[must_use]
+    pub fn merge_rel(&self, existing: Option<&str>) -> String {
+        crate::merge_tokens(existing, self.rel.iter().copied())
+    }
+}
+
+impl Default for ExternalLinks<'static> {
+    fn default() -> Self {
+        Self {
+            internal_hosts: HashSet::default(),
+            rel: vec!["nofollow", "ugc", "noopener", "noreferrer"],
+            target_blank: false,
+        }
+    }
+}
+
+/// Byte range of the host in a URL
+#[inline]
+pub(crate) fn host_range(url: &str) -> Option<Range<usize>> {
+    let start = url.find("://")? + "://".len();
+    let authority_len = url[start..]
+        .find(['/', '?', '#', '\\'])
+        .unwrap_or(url.len() - start);
+    let authority = &url[start..start + authority_len];
+
+    let host_start = authority.rfind('@').map_or(0, |at| at + 1);
+    let host = &authority[host_start..];
+    let host_len = if host.starts_with('[') {
+        host.find(']').map_or(host.len(), |end| end + 1)
+    } else {
+        host.find(':').unwrap_or(host.len())
+    };
+
+    let start = start + host_start;
+    Some(start..start + host_len)
+}
@@ -0,0 +1,79 @@
+/// Presentation `MathML` elements
+///
+/// `annotation-xml`, `malignmark` and `mglyph` are left out on purpose, since they are commonly used for mutation XSS
+#[rustfmt::skip]
+pub(crate) const MATHML_ELEMENTS: &[&str] = &[
+    "annotation", "maction", "math", "menclose", "merror", "mfrac", "mi", "mmultiscripts", "mn",
+    "mo", "mover", "mpadded", "mphantom", "mprescripts", "mroot", "mrow", "ms", "mspace", "msqrt",
+    "mstyle", "msub", "msubsup", "msup", "mtable", "mtd", "mtext", "mtr", "munder", "munderover",
+    "none", "semantics",
+];
+
+/// `MathML` elements whose content is parsed as HTML
+const MATHML_TEXT_INTEGRATION_POINTS: &[&str] = &["mi", "mn", "mo", "ms", "mtext"];
+
+/// SVG elements whose content is parsed as HTML
+const SVG_HTML_INTEGRATION_POINTS: &[&str] = &["desc", "foreignobject", "title"];
+
+/// Elements whose content is parsed as text in HTML, but as markup in foreign content
+///
+/// Since the same markup is parsed differently depending on the namespace, these are never allowed in foreign content.
+/// `title` is left out, since it is an actual SVG element
+pub(crate) const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "iframe",
+    "noembed",
+    "noframes",
+    "noscript",
+    "plaintext",
+    "script",
+    "style",
+    "textarea",
+    "xmp",
+];
+
+/// Namespace of an element
+///
+/// The namespace is determined by the elements that end up in the output, the same way a browser would when parsing the output.
+/// We can't rely on the namespaces reported by `lol_html`, since it only approximates the tree construction
+/// (for example, integration points are put into the HTML namespace) and doesn't know which elements we removed
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum Namespace {
+    /// HTML namespace
+    #[default]
+    Html,
+
+    /// `MathML` namespace
+    MathMl,
+
+    /// SVG namespace
+    Svg,
+}
+
+impl Namespace {
+    /// Namespace a browser puts the element into, given the parent element in the output
+    #[inline]
+    pub(crate) fn of_child(parent: Option<(&str, Self)>, tag_name: &str) -> Self {
+        match parent {
+            Some((parent_name, Self::MathMl))
+                if !MATHML_TEXT_INTEGRATION_POINTS.contains(&parent_name)
+                    || matches!(tag_name, "malignmark" | "mglyph") =>
+            {
+                if parent_name == "annotation-xml" && tag_name == "svg" {
+                    Self::Svg
+                } else {
+                    Self::MathMl
+                }
+            }
+            Some((parent_name, Self::Svg))
+                if !SVG_HTML_INTEGRATION_POINTS.contains(&parent_name) =>
+            {
+                Self::Svg
+            }
+            _ => match tag_name {
+                "math" => Self::MathMl,
+                "svg" => Self::Svg,
+                _ => Self::Html,
+            },
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use crate::{
+    hashmap, hashset, namespace::MATHML_ELEMENTS, BubbleBath, CssSanitizer, CssValue, ElementHook,
+    ElementView, Namespace,
+};
+use ahash::{HashMap, HashSet};
+use lol_html::HandlerResult;
+
+/// Check whether the class is one of the classes used by Mastodon-compatible servers
+#[inline]
+fn is_activitypub_class(class: &str) -> bool {
+    ["h-", "p-", "u-", "dt-", "e-"]
+        .iter()
+        .any(|prefix| class.starts_with(prefix))
+        || matches!(class, "ellipsis" | "hashtag" | "invisible" | "mention")
+}
+
+/// Remove all classes that aren't microformats or used for the rendering of mentions and links
+#[inline]
+fn filter_activitypub_classes(element: &mut ElementView<'_, '_, '_>) -> HandlerResult {
+    let Some(class) = element.get_attribute("class") else {
+        return Ok(());
+    };
+
+    let classes = class
+        .split_ascii_whitespace()
+        .filter(|class| is_activitypub_class(class))
+        .collect::<Vec<_>>();
+
+    if classes.is_empty() {
+        element.remove_attribute("class");
+    } else {
+        element.set_attribute("class", &classes.join(" "))?;
+    }
+
+    Ok(())
+}
+
+/// Attributes specific to a presentation `MathML` element
+#[rustfmt::skip]
+fn mathml_attributes(tag_name: &str) -> &'static [&'static str] {
+    match tag_name {
+        "annotation" => &["encoding"],
+        "maction" => &["actiontype", "selection"],
+        "math" => &["alttext", "display", "xmlns"],
+        "menclose" => &["notation"],
+        "mfrac" => &["denomalign", "linethickness", "numalign"],
+        "mo" => &[
+            "accent", "fence", "form", "largeop", "lspace", "maxsize", "minsize", "movablelimits",
+            "rspace", "separator", "stretchy", "symmetric",
+        ],
+        "mover" => &["accent"],
+        "mpadded" => &["depth", "height", "lspace", "voffset", "width"],
+        "mspace" => &["depth", "height", "width"],
+        "mtable" => &[
+            "columnalign", "columnlines", "columnspacing", "frame", "rowalign", "rowlines",
+            "rowspacing", "width",
+        ],
+        "mtd" => &["columnalign", "columnspan", "rowalign", "rowspan"],
+        "mtr" => &["columnalign", "rowalign"],
+        "munder" => &["accentunder"],
+        "munderover" => &["accent", "accentunder"],
+        _ => &[],
+    }
+}
+
+impl BubbleBath<'static> {
+    /// Policy that removes all tags and only keeps the text
+    ///
+    /// The content of `<script>` and `<style>` tags is removed as well
+    #[must_use]
+    pub fn text_only() -> Self {
+        Self {
+            allowed_generic_attributes: HashSet::default(),
+            allowed_tags: HashSet::default(),
+            allowed_tag_attributes: HashMap::default(),
+            clean_url_attributes: HashMap::default(),
+            set_tag_attributes: HashMap::default(),
+            ..Self::default()
+        }
+    }
+
+    /// Policy for basic inline formatting, paragraphs and links
+    ///
+    /// Keeps `a`, `b`, `br`, `em`, `i`, `p`, and `strong` tags. Links can only use the `http`, `https`, and `mailto` schemes
+    #[must_use]
+    pub fn basic_formatting() -> Self {
+        Self {
+            allowed_generic_attributes: HashSet::default(),
+            allowed_tags: hashset!["a", "b", "br", "em", "i", "p", "strong"],
+            allowed_tag_attributes: hashmap![
+                "a" => hashset!["href"],
+            ],
+            allowed_url_schemes: hashset!["http", "https", "mailto"],
+            clean_url_attributes: hashmap![
+                "a" => hashset!["href"],
+            ],
+            ..Self::default()
+        }
+    }
+
+    /// Policy for rich user content (tables, images, code blocks, etc.)
+    ///
+    /// Extends [`BubbleBath::default`] with a few more tags and attributes, `class` attributes on code blocks (for syntax highlighting)
+    /// and lazy loading of images
+    #[must_use]
+    pub fn relaxed() -> Self {
+        let mut bubble_bath = Self::default();
+
+        bubble_bath
+            .allowed_tags
+            .extend(["caption", "picture", "section", "source", "tfoot"]);
+
+        let allowed_tag_attributes: HashMap<_, HashSet<_>> = hashmap![
+            "code" => hashset!["class"],
+            "img" => hashset!["decoding", "loading"],
+            "li" => hashset!["value"],
+            "ol" => hashset!["reversed", "type"],
+            "pre" => hashset!["class"],
+            "source" => hashset!["media", "sizes", "srcset", "type"],
+            "td" => hashset!["height", "valign", "width"],
+            "th" => hashset!["height", "valign", "width"],
+            "time" => hashset!["datetime"],
+        ];
+        for (tag_name, attributes) in allowed_tag_attributes {
+            bubble_bath
+                .allowed_tag_attributes
+                .entry(tag_name)
+                .or_default()
+                .extend(attributes);
+        }
+
+        bubble_bath.default_tag_attributes = hashmap![
+            "img" => hashmap![
+                "decoding" => "async",
+                "loading" => "lazy",
+            ],
+        ];
+        bubble_bath
+            .clean_url_attributes
+            .insert("source", hashset!["srcset"]);
+
+        bubble_bath
+    }
+
+    /// Policy for HTML emails
+    ///
+    /// Keeps the legacy presentational tags and attributes (`font`, `center`, `bgcolor`, `cellpadding`, etc.) emails are still built with.
+    /// `style` attributes are filtered through a [`CssSanitizer`] which allows the common layout and typography properties
+    #[must_use]
+    pub fn email() -> Self {
+        let mut bubble_bath = Self::default();
+
+        bubble_bath.allowed_tags.extend(["font", "tfoot"]);
+
+        let presentational_attributes = ["align", "bgcolor", "border", "height", "valign", "width"];
+        for tag_name in [
+            "div", "p", "table", "tbody", "td", "tfoot", "th", "thead", "tr",
+        ] {
+            bubble_bath
+                .allowed_tag_attributes
+                .entry(tag_name)
+                .or_default()
+                .extend(presentational_attributes);
+        }
+
+        let allowed_tag_attributes: HashMap<_, HashSet<_>> = hashmap![
+            "font" => hashset!["color", "face", "size"],
+            "img" => hashset!["border", "hspace", "vspace"],
+            "table" => hashset!["cellpadding", "cellspacing"],
+            "td" => hashset!["nowrap"],
+            "th" => hashset!["nowrap"],
+        ];
+        for (tag_name, attributes) in allowed_tag_attributes {
+            bubble_bath
+                .allowed_tag_attributes
+                .entry(tag_name)
+                .or_default()
+                .extend(attributes);
+        }
+
+        let allowed_properties: HashMap<_, _> = hashmap![
+            "border" => CssValue::Any,
+            "border-collapse" => CssValue::Keywords(hashset!["collapse", "separate"]),
+            "display" => CssValue::Keywords(hashset![
+                "block", "inline", "inline-block", "none", "table", "table-cell", "table-row",
+            ]),
+            "font-family" => CssValue::Any,
+            "font-size" => CssValue::Length,
+            "height" => CssValue::Length,
+            "line-height" => CssValue::Length,
+            "margin" => CssValue::Length,
+            "max-width" => CssValue::Length,
+            "padding" => CssValue::Length,
+            "vertical-align" => CssValue::Keywords(hashset!["baseline", "bottom", "middle", "top"]),
+            "width" => CssValue::Length,
+        ];
+        let mut css_sanitizer = CssSanitizer::default();
+        css_sanitizer.allowed_properties.extend(allowed_properties);
+
+        bubble_bath.allowed_url_schemes = hashset!["http", "https", "mailto", "tel"];
+        bubble_bath.css_sanitizer = Some(css_sanitizer);
+        bubble_bath.remove_content_tags.extend(["head", "title"]);
+
+        bubble_bath
+    }
+
+    /// Policy for federated content, compatible with the subset of HTML Mastodon keeps
+    ///
+    /// Only microformat classes (`h-*`, `p-*`, `u-*`, `dt-*`, `e-*`) and the classes used for mentions, hashtags and shortened links are kept.
+    /// All links are opened in a new tab and get `rel="nofollow noopener noreferrer"` merged into their `rel` attribute
+    #[must_use]
+    pub fn activitypub() -> Self {
+        #[rustfmt::skip]
+        let allowed_tags = hashset![
+            "a", "abbr", "b", "blockquote", "br", "code", "del", "em", "i", "li", "ol", "p",
+            "pre", "rp", "rt", "ruby", "s", "span", "strong", "u", "ul",
+        ];
+        let allowed_tag_attributes = hashmap![
+            "a" => hashset!["class", "href", "rel", "translate"],
+            "li" => hashset!["value"],
+            "ol" => hashset!["reversed", "start"],
+            "span" => hashset!["class", "translate"],
+        ];
+        let allowed_url_schemes = hashset![
+            "dat", "dweb", "gemini", "gopher", "http", "https", "ipfs", "ipns", "magnet", "ssb",
+            "xmpp",
+        ];
+        let element_hooks = hashmap![
+            "a" => vec![Box::new(filter_activitypub_classes) as ElementHook<'_>],
+            "span" => vec![Box::new(filter_activitypub_classes) as ElementHook<'_>],
+        ];
+        let merge_tag_attributes = hashmap![
+            "a" => hashmap![
+                "rel" => "nofollow noopener noreferrer",
+            ],
+        ];
+        let set_tag_attributes = hashmap![
+            "a" => hashmap![
+                "target" => "_blank",
+            ],
+        ];
+
+        Self {
+            allowed_generic_attributes: HashSet::default(),
+            allowed_tags,
+            allowed_tag_attributes,
+            allowed_url_schemes,
+            clean_url_attributes: hashmap![
+                "a" => hashset!["href"],
+            ],
+            element_hooks,
+            merge_tag_attributes,
+            set_tag_attributes,
+            ..Self::default()
+        }
+    }
+
+    /// Policy for presentation `MathML` (for example, formulas)
+    ///
+    /// Only allows `MathML`, so you want to combine it with another policy (for example, `BubbleBath::default().union(&BubbleBath::mathml())`).
+    ///
+    /// `annotation-xml`, `malignmark` and `mglyph` are never allowed. Inside of `MathML`, elements that aren't allowed are removed including their content
+    #[must_use]
+    pub fn mathml() -> Self {
+        let common_attributes = [
+            "dir",
+            "displaystyle",
+            "mathbackground",
+            "mathcolor",
+            "mathsize",
+            "mathvariant",
+            "scriptlevel",
+        ];
+
+        let allowed_tag_attributes = MATHML_ELEMENTS
+            .iter()
+            .map(|tag_name| {
+                let attributes = common_attributes
+                    .iter()
+                    .chain(mathml_attributes(tag_name))
+                    .copied()
+                    .collect();
+
+                (*tag_name, attributes)
+            })
+            .collect();
+
+        Self {
+            allowed_foreign_tags: hashmap![
+                Namespace::MathMl => MATHML_ELEMENTS.iter().copied().collect(),
+            ],
+            allowed_foreign_tag_attributes: hashmap![
+                Namespace::MathMl => allowed_tag_attributes,
+            ],
+            ..Self::empty()
+        }
+    }
+}
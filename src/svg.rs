@@ -0,0 +1,266 @@
+use crate::{entities, hashmap, hashset, url_scheme};
+use ahash::{HashMap, HashSet};
+use lol_html::{html_content::Element, HandlerResult};
+
+/// Attributes that reference other resources
+const REFERENCE_ATTRIBUTES: &[&str] = &["href", "xlink:href"];
+
+/// Elements that animate the value of another attribute
+const ANIMATION_TAGS: &[&str] = &["animate", "animatetransform", "set"];
+
+/// Policy for inline SVG
+///
+/// Only applies to elements in the SVG namespace (everything inside of an `svg` tag, except for the content of `foreignObject` tags).
+/// Tag and attribute names are matched in lowercase (for example, `lineargradient` and `viewbox`).
+///
+/// Elements that aren't allowed are removed including their content, since their content would otherwise end up in the wrong namespace
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SvgPolicy<'a> {
+    /// Presentation attributes you want to keep on all SVG elements
+    pub allowed_attributes: HashSet<&'a str>,
+
+    /// SVG elements you want to keep
+    ///
+    /// Elements whose content is parsed as text in HTML (for example, `style` and `script`) are never kept
+    pub allowed_tags: HashSet<&'a str>,
+
+    /// Attributes you want to keep on a per-element basis
+    pub allowed_tag_attributes: HashMap<&'a str, HashSet<&'a str>>,
+
+    /// Schemes you want to allow in `href` and `xlink:href` attributes
+    ///
+    /// By default this is empty, meaning only references to fragments in the same document (`#id`) are allowed.
+    /// The same goes for `url()` references in attribute values, which are always restricted to fragments
+    pub allowed_url_schemes: HashSet<&'a str>,
+}
+
+/// Check whether all `url()` references in the value point to fragments
+///
+/// Values containing CSS escapes are rejected, since they can spell out `url(` in other ways (for example, `\75 rl(`)
+#[inline]
+fn only_fragment_urls(value: &str) -> bool {
+    if value.contains('\\') {
+        return false;
+    }
+
+    let lowercase = value.to_ascii_lowercase();
+    lowercase.match_indices("url(").all(|(start, function)| {
+        lowercase[start + function.len()..]
+            .trim_start_matches(|chr: char| chr.is_ascii_whitespace() || chr == '"' || chr == '\'')
+            .starts_with('#')
+    })
+}
+
+impl SvgPolicy<'_> {
+    /// Check whether the attribute is allowed on the SVG element
+    #[inline]
+    #[must_use]
+    pub fn is_attribute_allowed(&self, tag_name: &str, attribute_name: &str) -> bool {
+        self.allowed_attributes.contains(attribute_name)
+            || self
+                .allowed_tag_attributes
+                .get(tag_name)
+                .is_some_and(|attributes| attributes.contains(attribute_name))
+    }
+
+    /// Check whether the value of an `href` or `xlink:href` attribute is allowed
+    #[inline]
+    #[must_use]
+    pub fn is_reference_allowed(&self, url: &str) -> bool {
+        url.starts_with('#')
+            || url_scheme(url).is_some_and(|scheme| self.allowed_url_schemes.contains(scheme))
+    }
+
+    /// Check whether the animation element targets an attribute it is allowed to change
+    ///
+    /// Animations can't target references, since they could be used to set them to `javascript:` URLs
+    #[inline]
+    fn is_animation_allowed(&self, element: &Element<'_, '_>) -> bool {
+        element
+            .get_attribute("attributename")
+            .map_or(true, |target| {
+                let target = entities::decode(target.trim()).to_ascii_lowercase();
+                !REFERENCE_ATTRIBUTES.contains(&target.as_str())
+                    && self.allowed_attributes.contains(target.as_str())
+            })
+    }
+
+    /// Remove all attributes that aren't allowed and the references that point to disallowed URLs
+    ///
+    /// If an ID prefix is set, IDs and the fragment references to them are prefixed
+    pub(crate) fn clean_element(
+        &self,
+        element: &mut Element<'_, '_>,
+        tag_name: &str,
+        id_prefix: Option<&str>,
+    ) -> HandlerResult {
+        if ANIMATION_TAGS.contains(&tag_name) && !self.is_animation_allowed(element) {
+            element.remove();
+            return Ok(());
+        }
+
+        let mut remove_attributes = Vec::new();
+        let mut prefixed_attributes = Vec::new();
+        for attribute in element.attributes() {
+            let name = attribute.name();
+            if !self.is_attribute_allowed(tag_name, &name) {
+                remove_attributes.push(name);
+                continue;
+            }
+
+            let value = entities::decode(&attribute.value()).into_owned();
+            let allowed = if REFERENCE_ATTRIBUTES.contains(&name.as_str()) {
+                self.is_reference_allowed(value.trim())
+            } else {
+                only_fragment_urls(&value)
+            };
+
+            if !allowed {
+                remove_attributes.push(name);
+                continue;
+            }
+
+            // Don't prefix values twice, so sanitizing our own output doesn't change it
+            if let Some(prefix) = id_prefix {
+                let prefixed = if name == "id" {
+                    format!("{prefix}{}", value.strip_prefix(prefix).unwrap_or(&value))
+                } else if let Some(fragment) = value
+                    .strip_prefix('#')
+                    .filter(|_| REFERENCE_ATTRIBUTES.contains(&name.as_str()))
+                {
+                    format!(
+                        "#{prefix}{}",
+                        fragment.strip_prefix(prefix).unwrap_or(fragment)
+                    )
+                } else if value.contains("url(#") {
+                    let prefixed_url = format!("url(#{prefix}");
+                    value
+                        .replace(&prefixed_url, "url(#")
+                        .replace("url(#", &prefixed_url)
+                } else {
+                    continue;
+                };
+
+                prefixed_attributes.push((name, prefixed));
+            }
+        }
+
+        for name in remove_attributes {
+            element.remove_attribute(&name);
+        }
+
+        for (name, value) in prefixed_attributes {
+            element.set_attribute(&name, &value.replace('&', "&amp;"))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SvgPolicy<'static> {
+    #[allow(clippy::too_many_lines)]
+    fn default() -> Self {
+        #[rustfmt::skip]
+        let allowed_tags = hashset![
+            "animate", "animatetransform", "circle", "clippath", "defs", "desc", "ellipse", "g",
+            "line", "lineargradient", "marker", "mask", "path", "pattern", "polygon", "polyline",
+            "radialgradient", "rect", "set", "stop", "svg", "symbol", "text", "textpath", "title",
+            "tspan", "use",
+        ];
+        #[rustfmt::skip]
+        let allowed_attributes = hashset![
+            "clip-path", "clip-rule", "color", "display", "dominant-baseline", "fill",
+            "fill-opacity", "fill-rule", "font-family", "font-size", "font-style", "font-weight",
+            "id", "marker-end", "marker-mid", "marker-start", "mask", "opacity", "stroke",
+            "stroke-dasharray", "stroke-dashoffset", "stroke-linecap", "stroke-linejoin",
+            "stroke-miterlimit", "stroke-opacity", "stroke-width", "text-anchor", "transform",
+            "vector-effect", "visibility",
+        ];
+        let allowed_tag_attributes = hashmap![
+            "animate" => hashset![
+                "attributename", "begin", "calcmode", "dur", "end", "from", "keytimes",
+                "repeatcount", "to", "values"
+            ],
+            "animatetransform" => hashset![
+                "attributename", "begin", "calcmode", "dur", "end", "from", "keytimes",
+                "repeatcount", "to", "type", "values"
+            ],
+            "circle" => hashset![
+                "cx", "cy", "pathlength", "r"
+            ],
+            "clippath" => hashset![
+                "clippathunits"
+            ],
+            "ellipse" => hashset![
+                "cx", "cy", "pathlength", "rx", "ry"
+            ],
+            "line" => hashset![
+                "pathlength", "x1", "x2", "y1", "y2"
+            ],
+            "lineargradient" => hashset![
+                "gradienttransform", "gradientunits", "href", "spreadmethod", "x1", "x2",
+                "xlink:href", "y1", "y2"
+            ],
+            "marker" => hashset![
+                "markerheight", "markerunits", "markerwidth", "orient", "preserveaspectratio",
+                "refx", "refy", "viewbox"
+            ],
+            "mask" => hashset![
+                "height", "maskcontentunits", "maskunits", "width", "x", "y"
+            ],
+            "path" => hashset![
+                "d", "pathlength"
+            ],
+            "pattern" => hashset![
+                "height", "href", "patterncontentunits", "patterntransform", "patternunits",
+                "preserveaspectratio", "viewbox", "width", "x", "xlink:href", "y"
+            ],
+            "polygon" => hashset![
+                "pathlength", "points"
+            ],
+            "polyline" => hashset![
+                "pathlength", "points"
+            ],
+            "radialgradient" => hashset![
+                "cx", "cy", "fr", "fx", "fy", "gradienttransform", "gradientunits", "href", "r",
+                "spreadmethod", "xlink:href"
+            ],
+            "rect" => hashset![
+                "height", "pathlength", "rx", "ry", "width", "x", "y"
+            ],
+            "set" => hashset![
+                "attributename", "begin", "dur", "end", "to"
+            ],
+            "stop" => hashset![
+                "offset", "stop-color", "stop-opacity"
+            ],
+            "svg" => hashset![
+                "height", "preserveaspectratio", "version", "viewbox", "width", "xmlns",
+                "xmlns:xlink"
+            ],
+            "symbol" => hashset![
+                "height", "preserveaspectratio", "viewbox", "width", "x", "y"
+            ],
+            "text" => hashset![
+                "dx", "dy", "lengthadjust", "rotate", "textlength", "x", "y"
+            ],
+            "textpath" => hashset![
+                "href", "lengthadjust", "method", "side", "spacing", "startoffset", "textlength",
+                "xlink:href"
+            ],
+            "tspan" => hashset![
+                "dx", "dy", "lengthadjust", "rotate", "textlength", "x", "y"
+            ],
+            "use" => hashset![
+                "height", "href", "width", "x", "xlink:href", "y"
+            ],
+        ];
+
+        Self {
+            allowed_attributes,
+            allowed_tags,
+            allowed_tag_attributes,
+            allowed_url_schemes: HashSet::default(),
+        }
+    }
+}
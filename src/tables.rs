@@ -0,0 +1,176 @@
+use crate::{entities, namespace::Namespace, State};
+use lol_html::{
+    html_content::{ContentType, Element},
+    HandlerResult,
+};
+use std::fmt::Write;
+
+/// Elements that determine where table elements are valid
+const CONTEXT_ELEMENTS: &[&str] = &[
+    "caption", "colgroup", "table", "tbody", "td", "tfoot", "th", "thead", "tr",
+];
+
+/// Elements that are only valid at certain positions inside of tables
+const STRUCTURE_ELEMENTS: &[&str] = &[
+    "caption", "col", "colgroup", "tbody", "td", "tfoot", "th", "thead", "tr",
+];
+
+/// Configuration of the table structure repair
+///
+/// If set, missing `tbody`, `tr` and `colgroup` wrappers are inserted, cells and rows that aren't closed are closed explicitly,
+/// and table elements outside of tables are removed while keeping their content
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TableRepair {
+    /// Maximum value of `colspan` attributes (values below 1 are treated as 1)
+    pub max_colspan: u32,
+
+    /// Maximum value of `rowspan` attributes
+    pub max_rowspan: u32,
+}
+
+impl TableRepair {
+    /// Clamp the `colspan` and `rowspan` attributes of the cell, removing them if they aren't numbers
+    pub(crate) fn clamp_spans(self, element: &mut Element<'_, '_>) -> HandlerResult {
+        // A `rowspan` of zero spans all remaining rows of the section
+        for (name, min, max) in [
+            ("colspan", 1, self.max_colspan),
+            ("rowspan", 0, self.max_rowspan),
+        ] {
+            let Some(value) = element.get_attribute(name) else {
+                continue;
+            };
+
+            // Browsers only look at the leading digits
+            let value = entities::decode(&value);
+            let value = value.trim_start();
+            let end = value
+                .find(|chr: char| !chr.is_ascii_digit())
+                .unwrap_or(value.len());
+
+            if end == 0 {
+                element.remove_attribute(name);
+                continue;
+            }
+
+            let span = value[..end]
+                .parse()
+                .unwrap_or(u32::MAX)
+                .clamp(min, max.max(min));
+            element.set_attribute(name, &span.to_string())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for TableRepair {
+    fn default() -> Self {
+        Self {
+            max_colspan: 100,
+            max_rowspan: 100,
+        }
+    }
+}
+
+impl State {
+    /// Insert the missing wrappers around the table element and close the elements it closes implicitly
+    ///
+    /// Returns `false` if the element isn't inside of a table
+    pub(crate) fn repair_table(&self, element: &mut Element<'_, '_>, tag_name: &str) -> bool {
+        if !STRUCTURE_ELEMENTS.contains(&tag_name) {
+            return true;
+        }
+
+        let mut insert = String::new();
+        let wrappers: &[&str] = loop {
+            let elements = self.elements.borrow();
+            let Some(context_idx) = elements
+                .iter()
+                .rposition(|name| CONTEXT_ELEMENTS.contains(&name.as_str()))
+            else {
+                drop(elements);
+                element.before(&insert, ContentType::Html);
+                return false;
+            };
+
+            match (elements[context_idx].as_str(), tag_name) {
+                ("table", "caption" | "colgroup" | "tbody" | "tfoot" | "thead")
+                | ("tbody" | "tfoot" | "thead", "tr")
+                | ("tr", "td" | "th")
+                | ("colgroup", "col") => break &[],
+                ("table", "col") => break &["colgroup"],
+                ("table", "tr") => break &["tbody"],
+                ("table", "td" | "th") => break &["tbody", "tr"],
+                ("tbody" | "tfoot" | "thead", "td" | "th") => break &["tr"],
+                _ => {
+                    drop(elements);
+                    insert.push_str(&self.close_elements(context_idx));
+                }
+            }
+        };
+
+        // If we held back the start tag of the table, the wrappers might end up empty as well
+        let defer = !self.deferred_start_tags.borrow().is_empty();
+        for name in wrappers {
+            let depth = self.push_element((*name).to_string(), Namespace::Html);
+            self.wrappers.borrow_mut().push(depth);
+
+            if defer {
+                self.deferred_start_tags
+                    .borrow_mut()
+                    .push((depth, format!("<{name}>")));
+            } else {
+                let _ = write!(insert, "<{name}>");
+                self.trailing_whitespace.set(false);
+            }
+        }
+
+        element.before(&insert, ContentType::Html);
+        true
+    }
+
+    /// Close all elements from the depth onwards, returning their end tags (innermost first)
+    pub(crate) fn close_elements(&self, depth: usize) -> String {
+        let mut closing = String::new();
+        for (element_depth, name) in self.elements.borrow().iter().enumerate().skip(depth).rev() {
+            // Elements whose start tag we held back are empty, so we drop them
+            if !self.discard_start_tag(element_depth) {
+                let _ = write!(closing, "</{name}>");
+            }
+        }
+
+        self.truncate_elements(depth);
+        self.wrappers
+            .borrow_mut()
+            .retain(|wrapper_depth| *wrapper_depth < depth);
+
+        if !closing.is_empty() {
+            self.trailing_whitespace.set(false);
+        }
+
+        closing
+    }
+
+    /// End tags of the wrappers we inserted from the depth onwards, innermost first
+    pub(crate) fn close_wrappers(&self, depth: usize) -> String {
+        let elements = self.elements.borrow();
+        let mut wrappers = self.wrappers.borrow_mut();
+
+        let mut closing = String::new();
+        while let Some(&wrapper_depth) = wrappers
+            .last()
+            .filter(|wrapper_depth| **wrapper_depth >= depth)
+        {
+            wrappers.pop();
+            if !self.discard_start_tag(wrapper_depth) {
+                let _ = write!(closing, "</{}>", elements[wrapper_depth]);
+            }
+        }
+
+        if !closing.is_empty() {
+            self.trailing_whitespace.set(false);
+        }
+
+        closing
+    }
+}
@@ -0,0 +1,189 @@
+use std::{fmt::Write, ops::Range};
+
+/// Token recognized in a text node
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Token {
+    /// Hashtag (`#tag`), the name is stored without the leading `#`
+    Hashtag {
+        /// Name of the hashtag
+        name: String,
+    },
+
+    /// Mention of a user (`@user` or `@user@host`)
+    Mention {
+        /// Username of the mentioned user
+        username: String,
+
+        /// Host of the mentioned user, if the mention was qualified with one
+        host: Option<String>,
+    },
+}
+
+/// Hook recognizing tokens in text
+///
+/// Receives a piece of text and returns the byte range and the value of the first token in it
+pub type TokenRecognizer<'a> =
+    Box<dyn Fn(&str) -> Option<(Range<usize>, Token)> + Send + Sync + 'a>;
+
+/// Configuration for turning tokens in text into links
+///
+/// The templates can contain placeholders which are replaced with the percent-encoded parts of the token:
+///
+/// - `{name}` in the hashtag template is replaced with the name of the hashtag
+/// - `{username}` and `{host}` in the mention template are replaced with the username and host of the mention (the host is empty for unqualified mentions)
+/// - `{acct}` in the mention template is replaced with either `username` or `username@host`
+pub struct TokenLinks<'a> {
+    /// URL template for hashtags (for example, `https://example.com/tags/{name}`)
+    pub hashtag_url: &'a str,
+
+    /// URL template for mentions (for example, `https://example.com/@{acct}`)
+    pub mention_url: &'a str,
+
+    /// Hook recognizing the tokens
+    pub recognizer: TokenRecognizer<'a>,
+}
+
+impl<'a> TokenLinks<'a> {
+    /// Construct a new configuration using the [default recognizer](recognize)
+    #[must_use]
+    pub fn new(hashtag_url: &'a str, mention_url: &'a str) -> Self {
+        Self {
+            hashtag_url,
+            mention_url,
+            recognizer: Box::new(recognize),
+        }
+    }
+
+    /// Construct the URL the token should link to
+    #[must_use]
+    pub fn url(&self, token: &Token) -> String {
+        match token {
+            Token::Hashtag { name } => fill_template(self.hashtag_url, &[("name", name)]),
+            Token::Mention { username, host } => {
+                let host = host.as_deref().unwrap_or_default();
+                let acct = if host.is_empty() {
+                    username.clone()
+                } else {
+                    format!("{username}@{host}")
+                };
+
+                fill_template(
+                    self.mention_url,
+                    &[("acct", &acct), ("host", host), ("username", username)],
+                )
+            }
+        }
+    }
+}
+
+/// Percent-encode everything except unreserved characters
+#[inline]
+fn percent_encode(value: &str) -> String {
+    let mut acc = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            acc.push(byte as char);
+        } else {
+            let _ = write!(acc, "%{byte:02X}");
+        }
+    }
+
+    acc
+}
+
+#[inline]
+fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut acc = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        acc.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end| {
+            values
+                .iter()
+                .find(|(placeholder, _value)| *placeholder == &rest[1..end])
+                .map(|(_placeholder, value)| (end, value))
+        });
+
+        if let Some((end, value)) = value {
+            acc.push_str(&percent_encode(value));
+            rest = &rest[end + 1..];
+        } else {
+            acc.push('{');
+            rest = &rest[1..];
+        }
+    }
+
+    acc.push_str(rest);
+    acc
+}
+
+#[inline]
+fn is_word_char(chr: char) -> bool {
+    chr.is_alphanumeric() || chr == '_'
+}
+
+/// Length of the name at the start of the input, allowing the delimiters inside of it
+#[inline]
+fn name_len(input: &str, delimiters: &[char]) -> usize {
+    let len = input
+        .find(|chr: char| !is_word_char(chr) && !delimiters.contains(&chr))
+        .unwrap_or(input.len());
+
+    input[..len].trim_end_matches(delimiters).len()
+}
+
+/// Default token recognizer
+///
+/// Recognizes hashtags (`#tag`) and mentions (`@user`, `@user@host`) in the style of Mastodon.
+/// Tokens have to start at a word boundary, hashtags can't consist of digits only
+#[must_use]
+pub fn recognize(text: &str) -> Option<(Range<usize>, Token)> {
+    text.match_indices(['#', '@']).find_map(|(start, sigil)| {
+        let preceded_by_word = text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|chr| is_word_char(chr) || matches!(chr, '#' | '@' | '/'));
+        if preceded_by_word {
+            return None;
+        }
+
+        let rest = &text[start + 1..];
+        if sigil == "#" {
+            let len = name_len(rest, &[]);
+            let name = &rest[..len];
+            if name.is_empty() || name.chars().all(|chr| chr.is_ascii_digit()) {
+                return None;
+            }
+
+            let token = Token::Hashtag {
+                name: name.to_string(),
+            };
+            return Some((start..start + 1 + len, token));
+        }
+
+        let username_len = name_len(rest, &['.', '-']);
+        if username_len == 0 {
+            return None;
+        }
+        let username = &rest[..username_len];
+        let mut end = start + 1 + username_len;
+
+        let host = text[end..]
+            .strip_prefix('@')
+            .map(|host| &host[..name_len(host, &['.', '-'])])
+            .filter(|host| host.contains('.'));
+        if let Some(host) = host {
+            end += 1 + host.len();
+        }
+
+        let token = Token::Mention {
+            username: username.to_string(),
+            host: host.map(ToString::to_string),
+        };
+        Some((start..end, token))
+    })
+}
@@ -0,0 +1,114 @@
+use std::borrow::Cow;
+
+/// Filter for Unicode characters that are commonly used for spoofing
+///
+/// Applied to text and to the values of the attributes we keep. Characters are checked after character references are decoded
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug)]
+pub struct UnicodeFilter {
+    /// Normalize text into Unicode Normalization Form C
+    #[cfg(feature = "unicode-normalization")]
+    pub normalize_nfc: bool,
+
+    /// Remove the bidirectional embedding, override, and isolate characters (U+202A to U+202E and U+2066 to U+2069)
+    ///
+    /// These are used for "Trojan Source" style attacks. The implicit marks (LRM, RLM, ALM) are kept
+    pub remove_bidi_controls: bool,
+
+    /// Remove control characters, except for tabs and line breaks
+    pub remove_control_characters: bool,
+
+    /// Remove invisible characters (zero-width space, word joiner, invisible operators, Hangul fillers, etc.)
+    ///
+    /// Zero-width joiners are only kept inside of emoji sequences. Zero-width non-joiners are kept since some scripts require them
+    pub remove_invisible_characters: bool,
+}
+
+impl Default for UnicodeFilter {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "unicode-normalization")]
+            normalize_nfc: false,
+            remove_bidi_controls: true,
+            remove_control_characters: true,
+            remove_invisible_characters: true,
+        }
+    }
+}
+
+#[inline]
+fn is_bidi_control(chr: char) -> bool {
+    matches!(chr, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+#[inline]
+fn is_control(chr: char) -> bool {
+    chr.is_control() && !matches!(chr, '\t' | '\n' | '\r' | '\u{0C}')
+}
+
+#[inline]
+fn is_invisible(chr: char) -> bool {
+    matches!(
+        chr,
+        '\u{115F}' | '\u{1160}' | '\u{180E}' | '\u{200B}' | '\u{200D}' | '\u{2060}'
+            ..='\u{2064}' | '\u{3164}' | '\u{FEFF}' | '\u{FFA0}'
+    )
+}
+
+/// Rough check whether the character can be part of an emoji sequence joined by a zero-width joiner
+#[inline]
+fn is_emoji_component(chr: char) -> bool {
+    matches!(
+        chr,
+        '\u{2300}'..='\u{23FF}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2B00}'..='\u{2BFF}'
+            | '\u{FE0F}'
+            | '\u{1F000}'..='\u{1FAFF}'
+    )
+}
+
+impl UnicodeFilter {
+    #[inline]
+    fn is_removed(&self, chr: char) -> bool {
+        (self.remove_bidi_controls && is_bidi_control(chr))
+            || (self.remove_control_characters && is_control(chr))
+            || (self.remove_invisible_characters && is_invisible(chr))
+    }
+
+    /// Filter the text
+    #[must_use]
+    pub fn filter<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut filtered = Cow::Borrowed(text);
+
+        if text.chars().any(|chr| self.is_removed(chr)) {
+            let mut acc = String::with_capacity(text.len());
+            let mut chars = text.chars().peekable();
+            let mut previous = None;
+
+            while let Some(chr) = chars.next() {
+                let keep_joiner = chr == '\u{200D}'
+                    && previous.is_some_and(is_emoji_component)
+                    && chars.peek().copied().is_some_and(is_emoji_component);
+
+                if keep_joiner || !self.is_removed(chr) {
+                    acc.push(chr);
+                    previous = Some(chr);
+                }
+            }
+
+            filtered = Cow::Owned(acc);
+        }
+
+        #[cfg(feature = "unicode-normalization")]
+        if self.normalize_nfc {
+            use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+            if is_nfc_quick(filtered.chars()) != IsNormalized::Yes {
+                filtered = Cow::Owned(filtered.nfc().collect());
+            }
+        }
+
+        filtered
+    }
+}
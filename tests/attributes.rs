@@ -0,0 +1,50 @@
+//! Tests for the attribute allow-lists
+
+use bubble_bath::{hashmap, hashset, BubbleBath};
+
+#[test]
+fn wildcard_generic_attributes() {
+    let bubble_bath = BubbleBath {
+        allowed_generic_attributes: hashset!["aria-*", "data-*", "role"],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<p role="note" aria-label="Note" data-id="1" datax="2" title="x">hi</p>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<p role="note" aria-label="Note" data-id="1">hi</p>"#
+    );
+}
+
+#[test]
+fn wildcard_tag_attributes() {
+    let bubble_bath = BubbleBath {
+        allowed_tag_attributes: hashmap![
+            "span" => hashset!["data-*"],
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<span data-id="1">a</span><p data-id="1">b</p>"#)
+        .unwrap();
+
+    assert_eq!(result, r#"<span data-id="1">a</span><p>b</p>"#);
+}
+
+#[test]
+fn wildcards_dont_match_event_handlers() {
+    let bubble_bath = BubbleBath {
+        allowed_generic_attributes: hashset!["*", "o*"],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<p onclick="evil()" onmouseover="evil()" other="1">hi</p>"#)
+        .unwrap();
+
+    assert_eq!(result, r#"<p other="1">hi</p>"#);
+}
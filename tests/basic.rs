@@ -1,3 +1,5 @@
+//! Basic snapshot tests
+
 use bubble_bath::BubbleBath;
 use insta::assert_snapshot;
 
@@ -29,3 +31,33 @@ fn preserve_escaped() {
         )
         .unwrap());
 }
+
+#[test]
+fn remove_implicitly_closed() {
+    let result = bubble_bath::clean("<p><font>text</p>after").unwrap();
+    assert_eq!(result, "<p>text</p>after");
+
+    let result = BubbleBath::default()
+        .union(&BubbleBath::mathml())
+        .clean("<form><math><mtext></form>x")
+        .unwrap();
+    assert_eq!(result, "<math><mtext></mtext></math>x");
+}
+
+#[test]
+fn split_references() {
+    let input = "<p>a &amp; b &amp c &notin; &notit; &#x41;&#65</p>";
+    let expected = bubble_bath::clean(input).unwrap();
+
+    for split in 0..=input.len() {
+        let mut result = Vec::new();
+        BubbleBath::default()
+            .clean_streaming(
+                [&input.as_bytes()[..split], &input.as_bytes()[split..]].into_iter(),
+                |out| result.extend_from_slice(out),
+            )
+            .unwrap();
+
+        assert_eq!(String::from_utf8(result).unwrap(), expected, "{split}");
+    }
+}
@@ -0,0 +1,139 @@
+//! Tests for removing empty tags, collapsing line breaks and collapsing whitespace
+
+use bubble_bath::{BubbleBath, TableRepair};
+
+#[test]
+fn remove_empty_tags() {
+    let bubble_bath = BubbleBath {
+        remove_empty_tags: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<p></p><div><p><script>alert(1)</script></p></div><ul><li></li><li>item</li></ul><p><b></b><i>text</i></p>")
+        .unwrap();
+    assert_eq!(result, "<ul><li>item</li></ul><p><i>text</i></p>");
+}
+
+#[test]
+fn keep_whitespace_of_empty_tags() {
+    let bubble_bath = BubbleBath {
+        remove_empty_tags: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("a<span> </span>b <div>\n<p>text</p></div>")
+        .unwrap();
+    assert_eq!(result, "a b \n<div><p>text</p></div>");
+}
+
+#[test]
+fn keep_allowed_empty_tags() {
+    let mut bubble_bath = BubbleBath {
+        remove_empty_tags: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<p><img src="https://example.com/a.png"></p><p><br></p><table><tr><td></td></tr></table>"#)
+        .unwrap();
+    assert_eq!(
+        result,
+        r#"<p><img src="https://example.com/a.png"></p><p><br></p><table><tr><td></td></tr></table>"#
+    );
+
+    bubble_bath.keep_empty_tags.insert("p");
+    assert_eq!(bubble_bath.clean("<p></p><div></div>").unwrap(), "<p></p>");
+}
+
+#[test]
+fn remove_empty_tables() {
+    let bubble_bath = BubbleBath {
+        remove_empty_tags: true,
+        table_repair: Some(TableRepair::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<table><tr></tr></table><table><tr><td>cell</td></tr></table>")
+        .unwrap();
+    assert_eq!(
+        result,
+        "<table><tbody><tr><td>cell</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn remove_unclosed_empty_tags() {
+    let bubble_bath = BubbleBath {
+        remove_empty_tags: true,
+        ..BubbleBath::default()
+    };
+
+    assert_eq!(bubble_bath.clean("<div><span>").unwrap(), "");
+    assert_eq!(
+        bubble_bath.clean("<div>text<span>").unwrap(),
+        "<div>text</div>"
+    );
+}
+
+#[test]
+fn keep_foreign_content() {
+    let bubble_bath = BubbleBath {
+        remove_empty_tags: true,
+        ..BubbleBath::mathml()
+    };
+
+    let input = "<math><mspace></mspace><mi>x</mi></math>";
+    assert_eq!(bubble_bath.clean(input).unwrap(), input);
+}
+
+#[test]
+fn collapse_line_breaks() {
+    let bubble_bath = BubbleBath {
+        max_line_breaks: Some(2),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("a<br><br><br><br>b<br> <br>\n<br/>c<p><br><br><br></p>")
+        .unwrap();
+    assert_eq!(result, "a<br><br>b<br> <br>\nc<p><br><br></p>");
+}
+
+#[test]
+fn collapse_whitespace() {
+    let bubble_bath = BubbleBath {
+        collapse_whitespace: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("text   with \n\t whitespace <pre>  kept\n  as is </pre> <code>a   b</code>")
+        .unwrap();
+    assert_eq!(
+        result,
+        "text with whitespace <pre>  kept\n  as is </pre> <code>a   b</code>"
+    );
+}
+
+#[test]
+fn collapse_whitespace_around_removed_tags() {
+    let bubble_bath = BubbleBath {
+        collapse_whitespace: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("a <script>alert(1)</script> b <b> c </b> d")
+        .unwrap();
+    assert_eq!(result, "a b <b> c </b> d");
+    assert_eq!(bubble_bath.clean(&result).unwrap(), result);
+}
+
+#[test]
+fn disabled_by_default() {
+    let input = "<p></p>a<br><br><br>  b";
+    assert_eq!(bubble_bath::clean(input).unwrap(), input);
+}
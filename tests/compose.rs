@@ -0,0 +1,92 @@
+//! Tests for the composition of policies
+
+use bubble_bath::{hashmap, hashset, BubbleBath};
+
+#[test]
+fn default_minus_img_plus_iframe() {
+    let bubble_bath = BubbleBath::default()
+        .subtract(&BubbleBath {
+            allowed_tags: hashset!["img"],
+            ..BubbleBath::empty()
+        })
+        .union(&BubbleBath {
+            allowed_tags: hashset!["iframe"],
+            allowed_tag_attributes: hashmap![
+                "iframe" => hashset!["src"],
+            ],
+            clean_url_attributes: hashmap![
+                "iframe" => hashset!["src"],
+            ],
+            ..BubbleBath::empty()
+        });
+
+    let result = bubble_bath
+        .clean(r#"<img src="https://example.com/a.png"><iframe src="https://www.youtube-nocookie.com/embed/x"></iframe><iframe src="javascript:alert(1)"></iframe>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<iframe src="https://www.youtube-nocookie.com/embed/x"></iframe><iframe></iframe>"#
+    );
+}
+
+#[test]
+fn remove_content_wins() {
+    let bubble_bath = BubbleBath::default().union(&BubbleBath {
+        allowed_tags: hashset!["script"],
+        ..BubbleBath::empty()
+    });
+
+    assert!(!bubble_bath.allowed_tags.contains("script"));
+    assert_eq!(bubble_bath.clean("<script>alert(1)</script>").unwrap(), "");
+}
+
+#[test]
+fn intersect() {
+    let bubble_bath = BubbleBath::default().intersect(&BubbleBath::basic_formatting());
+
+    assert_eq!(
+        bubble_bath.allowed_tags,
+        hashset!["a", "b", "br", "em", "i", "p", "strong"]
+    );
+    assert_eq!(
+        bubble_bath.allowed_tag_attributes,
+        hashmap![
+            "a" => hashset!["href"],
+        ]
+    );
+    assert_eq!(
+        bubble_bath.allowed_url_schemes,
+        hashset!["http", "https", "mailto"]
+    );
+    assert!(bubble_bath.allowed_generic_attributes.is_empty());
+    assert!(bubble_bath.clean_url_attributes["img"].contains("src"));
+}
+
+#[test]
+fn conflicting_set_attributes() {
+    let other = BubbleBath {
+        set_tag_attributes: hashmap![
+            "a" => hashmap![
+                "rel" => "nofollow",
+                "target" => "_blank",
+            ],
+        ],
+        ..BubbleBath::empty()
+    };
+
+    let union = BubbleBath::default().union(&other);
+    assert_eq!(
+        union.set_tag_attributes["a"],
+        hashmap!["rel" => "nofollow", "target" => "_blank"]
+    );
+
+    let intersection = BubbleBath::default().intersect(&other);
+    assert_eq!(
+        intersection.set_tag_attributes["a"],
+        hashmap!["rel" => "nofollow"]
+    );
+
+    let difference = BubbleBath::default().subtract(&other);
+    assert!(difference.set_tag_attributes.is_empty());
+}
@@ -0,0 +1,149 @@
+//! Tests for the content model enforcement
+
+use bubble_bath::{BubbleBath, ContentModel};
+
+#[test]
+fn keep_valid_structure() {
+    let input = "<ul><li>a</li></ul><dl><dt>t</dt><dd>d</dd><div><dt>t</dt><dd>d</dd></div></dl><figure><img><figcaption>c</figcaption></figure><details><summary>s</summary><p>t</p></details>";
+
+    for content_model in [ContentModel::Move, ContentModel::Unwrap] {
+        let bubble_bath = BubbleBath {
+            content_model: Some(content_model),
+            ..BubbleBath::default()
+        };
+
+        assert_eq!(bubble_bath.clean(input).unwrap(), input);
+    }
+}
+
+#[test]
+fn close_omitted_end_tags() {
+    for content_model in [ContentModel::Move, ContentModel::Unwrap] {
+        let bubble_bath = BubbleBath {
+            content_model: Some(content_model),
+            ..BubbleBath::default()
+        };
+
+        let result = bubble_bath
+            .clean("<ul><li>a<li>b<b>bold<li>c</ul><dl><dt>t<dd>d<dt>u</dl><p>a<p>b")
+            .unwrap();
+
+        assert_eq!(
+            result,
+            "<ul><li>a</li><li>b<b>bold</b></li><li>c</li></ul><dl><dt>t</dt><dd>d</dd><dt>u</dt></dl><p>a</p><p>b</p>"
+        );
+
+        let result = bubble_bath
+            .clean("<dl><dt>a<dd>b</dl><ul><li>a<ul><li>b</ul></ul><div><p>a<b>b</div>")
+            .unwrap();
+
+        assert_eq!(
+            result,
+            "<dl><dt>a</dt><dd>b</dd></dl><ul><li>a<ul><li>b</li></ul></li></ul><div><p>a<b>b</b></p></div>"
+        );
+    }
+}
+
+#[test]
+fn move_elements() {
+    let bubble_bath = BubbleBath {
+        content_model: Some(ContentModel::Move),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<p>a<div>b</div>c</p><a href=\"https://a.example\">one<a href=\"https://b.example\">two</a></a><ul><div><li>item</li></div></ul><figure><div><figcaption>c</figcaption></div></figure>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "<p>a</p><div>b</div>c<a href=\"https://a.example\" rel=\"noopener noreferrer\">one</a><a href=\"https://b.example\" rel=\"noopener noreferrer\">two</a><ul><div></div><li>item</li></ul><figure><div></div><figcaption>c</figcaption></figure>"
+    );
+}
+
+#[test]
+fn unwrap_elements() {
+    let bubble_bath = BubbleBath {
+        content_model: Some(ContentModel::Unwrap),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<p>a<div>b</div>c</p><a href=\"https://a.example\">one<a href=\"https://b.example\">two</a></a><ul><div><li>item</li></div></ul><figure><div><figcaption>c</figcaption></div></figure>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "<p>abc</p><a href=\"https://a.example\" rel=\"noopener noreferrer\">onetwo</a><ul><div>item</div></ul><figure><div>c</div></figure>"
+    );
+}
+
+#[test]
+fn unwrap_elements_without_valid_position() {
+    for content_model in [ContentModel::Move, ContentModel::Unwrap] {
+        let bubble_bath = BubbleBath {
+            content_model: Some(content_model),
+            ..BubbleBath::default()
+        };
+
+        let result = bubble_bath
+            .clean("<li>item</li><dd>d</dd><figcaption>c</figcaption><summary>s</summary><details><p>x</p><summary>late</summary></details>")
+            .unwrap();
+
+        assert_eq!(result, "itemdcs<details><p>x</p>late</details>");
+    }
+}
+
+#[test]
+fn keep_summary_as_first_child() {
+    let bubble_bath = BubbleBath {
+        content_model: Some(ContentModel::Unwrap),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<details><summary>s</summary>t<summary>u</summary></details>")
+        .unwrap();
+
+    assert_eq!(result, "<details><summary>s</summary>tu</details>");
+}
+
+#[test]
+fn respect_table_boundaries() {
+    let bubble_bath = BubbleBath {
+        content_model: Some(ContentModel::Move),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<ul><li><table><tr><td><li>x</li></td></tr></table></li></ul><p><table><tr><td><div>y</div></td></tr></table></p>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "<ul><li><table><tr><td>x</td></tr></table></li></ul><p><table><tr><td><div>y</div></td></tr></table></p>"
+    );
+}
+
+#[test]
+fn close_elements_opened_after_moved_elements() {
+    let bubble_bath = BubbleBath {
+        content_model: Some(ContentModel::Move),
+        ..BubbleBath::default()
+    };
+
+    for (input, expected) in [
+        ("<p>a<b>b<p>c</b></p>", "<p>a<b>b</b></p><p>c</p>"),
+        ("<p>a<div>b</p>c", "<p>a</p><div>b</div>c"),
+    ] {
+        let result = bubble_bath.clean(input).unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(bubble_bath.clean(&result).unwrap(), result);
+    }
+}
+
+#[test]
+fn disabled_by_default() {
+    let result = bubble_bath::clean("<li>item</li><p>a<div>b</div></p>").unwrap();
+    assert_eq!(result, "<li>item</li><p>a<div>b</div></p>");
+}
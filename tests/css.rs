@@ -0,0 +1,179 @@
+//! Tests for the inline style sanitizer
+
+use bubble_bath::{BubbleBath, CssSanitizer};
+use std::fs;
+
+#[test]
+fn keep_allowed_declarations() {
+    let bubble_bath = BubbleBath {
+        css_sanitizer: Some(CssSanitizer::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<p style="COLOR: #f00; text-align:center;font-weight: bold !important">hi</p>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<p style="color: #f00; text-align: center; font-weight: bold !important;">hi</p>"#
+    );
+}
+
+#[test]
+fn remove_disallowed_declarations() {
+    let bubble_bath = BubbleBath {
+        css_sanitizer: Some(CssSanitizer::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<p style="position: fixed; color: rgb(0, 0, 255); text-align: evil">hi</p>"#)
+        .unwrap();
+
+    assert_eq!(result, r#"<p style="color: rgb(0, 0, 255);">hi</p>"#);
+}
+
+#[test]
+fn remove_functions() {
+    let mut bubble_bath = BubbleBath {
+        css_sanitizer: Some(CssSanitizer::default()),
+        ..BubbleBath::default()
+    };
+
+    bubble_bath
+        .css_sanitizer
+        .as_mut()
+        .unwrap()
+        .allowed_properties
+        .insert("background-image", bubble_bath::CssValue::Any);
+
+    let result = bubble_bath
+        .clean(r#"<p style="background-image: url(javascript:alert(1)); color: expression(alert(1))">hi</p>"#)
+        .unwrap();
+    assert_eq!(result, "<p>hi</p>");
+
+    bubble_bath
+        .css_sanitizer
+        .as_mut()
+        .unwrap()
+        .allowed_functions
+        .insert("url");
+
+    let result = bubble_bath
+        .clean(r#"<p style="background-image: url('javascript:alert(1)')">hi</p><p style="background-image: url(https://example.com/a.png)">hi</p>"#)
+        .unwrap();
+    assert_eq!(
+        result,
+        r#"<p>hi</p><p style="background-image: url(https://example.com/a.png);">hi</p>"#
+    );
+}
+
+#[test]
+fn remove_escapes_and_comments() {
+    let bubble_bath = BubbleBath {
+        css_sanitizer: Some(CssSanitizer::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<p style="color: r\65 d; color: re/**/d; text-align: center">hi</p>"#)
+        .unwrap();
+
+    assert_eq!(result, r#"<p style="text-align: center;">hi</p>"#);
+}
+
+#[test]
+fn torture_inputs() {
+    let bubble_bath = BubbleBath {
+        css_sanitizer: Some(CssSanitizer::default()),
+        ..BubbleBath::default()
+    };
+
+    for input in ["div_expression", "div_bgimg", "style_attribute_comments"] {
+        let input = fs::read_to_string(format!("tests/inputs/{input}")).unwrap();
+        let result = bubble_bath.clean(&input).unwrap();
+
+        assert!(!result.contains("style"), "{result}");
+    }
+}
+
+#[test]
+fn sanitize_style_tags() {
+    let bubble_bath = BubbleBath {
+        css_sanitizer: Some(CssSanitizer::default()),
+        sanitize_style_tags: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(
+            r#"<style type="text/css">
+                @import url(https://evil.example/x.css);
+                p > b, .note { color: red; behavior: url(x.htc); -moz-binding: url(x.xml) }
+                a { background-image: url(javascript:alert(1)) }
+                @media (max-width: 600px) { p { text-align: center } }
+                @font-face { font-family: x; }
+            </style><p>hi</p>"#,
+        )
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "<style>p > b, .note { color: red; }\n@media (max-width: 600px) {\np { text-align: center; }\n}\n</style><p>hi</p>"
+    );
+}
+
+#[test]
+fn scope_style_tags() {
+    let bubble_bath = BubbleBath {
+        css_sanitizer: Some(CssSanitizer::default()),
+        sanitize_style_tags: true,
+        style_scope: Some("user-content"),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<style>p, .user-content b { color: red }</style>")
+        .unwrap();
+    assert_eq!(
+        result,
+        "<style>.user-content p, .user-content b { color: red; }\n</style>"
+    );
+
+    assert_eq!(bubble_bath.clean(&result).unwrap(), result);
+}
+
+#[test]
+fn scope_sibling_selectors() {
+    let bubble_bath = BubbleBath {
+        css_sanitizer: Some(CssSanitizer::default()),
+        sanitize_style_tags: true,
+        style_scope: Some("uc"),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<style>.uc ~ * { color: red } .uc + p, .uc > b { color: blue }</style>")
+        .unwrap();
+    assert_eq!(
+        result,
+        "<style>.uc .uc ~ * { color: red; }\n.uc .uc + p, .uc .uc > b { color: blue; }\n</style>"
+    );
+
+    assert_eq!(bubble_bath.clean(&result).unwrap(), result);
+}
+
+#[test]
+fn style_tags_cant_break_out() {
+    let bubble_bath = BubbleBath {
+        css_sanitizer: Some(CssSanitizer::default()),
+        sanitize_style_tags: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<style>p { color: "</style><script>alert(1)</script>" }</style>"#)
+        .unwrap();
+    assert!(!result.contains("script"), "{result}");
+}
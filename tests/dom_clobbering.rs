@@ -0,0 +1,72 @@
+//! Tests for the ID and name prefixing
+
+use bubble_bath::{hashset, BubbleBath};
+
+#[test]
+fn prefix_ids() {
+    let mut bubble_bath = BubbleBath {
+        allowed_generic_attributes: hashset!["id", "lang", "title"],
+        id_prefix: Some("user-content-"),
+        ..BubbleBath::default()
+    };
+    bubble_bath
+        .allowed_tag_attributes
+        .get_mut("a")
+        .unwrap()
+        .insert("name");
+
+    let result = bubble_bath
+        .clean(r#"<h2 id="intro">Intro</h2><a name="config">anchor</a>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<h2 id="user-content-intro">Intro</h2><a name="user-content-config" rel="noopener noreferrer">anchor</a>"#
+    );
+}
+
+#[test]
+fn rewrite_fragments() {
+    let bubble_bath = BubbleBath {
+        id_prefix: Some("user-content-"),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r##"<a href="#intro">jump</a><a href="#user-content-intro">jump</a>"##)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r##"<a href="#user-content-intro" rel="noopener noreferrer">jump</a><a href="#user-content-intro" rel="noopener noreferrer">jump</a>"##
+    );
+}
+
+#[test]
+fn remove_clobbering_values() {
+    let mut bubble_bath = BubbleBath {
+        allowed_generic_attributes: hashset!["id", "lang", "title"],
+        id_prefix: Some("user-content-"),
+        ..BubbleBath::default()
+    };
+    bubble_bath
+        .allowed_tag_attributes
+        .get_mut("a")
+        .unwrap()
+        .insert("name");
+
+    let result = bubble_bath
+        .clean(r#"<p id="location">x</p><p id="">y</p><a name="user-content-cookie">z</a>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<p>x</p><p>y</p><a rel="noopener noreferrer">z</a>"#
+    );
+}
+
+#[test]
+fn fragments_without_prefix() {
+    let result = bubble_bath::clean(r##"<a href="#intro">jump</a>"##).unwrap();
+    assert_eq!(result, r#"<a rel="noopener noreferrer">jump</a>"#);
+}
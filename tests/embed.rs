@@ -0,0 +1,81 @@
+//! Tests for the embed policy
+
+use bubble_bath::{hashset, BubbleBath, EmbedPolicy};
+
+#[test]
+fn allowed_embeds() {
+    let mut embed_policy = EmbedPolicy::default();
+    embed_policy
+        .allowed_sources
+        .extend(["*.peertube.example/videos/embed/"]);
+
+    let bubble_bath = BubbleBath {
+        embed_policy: Some(embed_policy),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<iframe src="https://www.youtube-nocookie.com/embed/abc" width="560" allow="fullscreen; camera; autoplay 'self'" onload="alert(1)"></iframe>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<iframe src="https://www.youtube-nocookie.com/embed/abc" width="560" allow="fullscreen; autoplay" sandbox="allow-popups allow-presentation allow-same-origin allow-scripts" referrerpolicy="strict-origin-when-cross-origin"></iframe>"#
+    );
+
+    let result = bubble_bath
+        .clean(r#"<iframe src="https://videos.peertube.example/videos/embed/1" sandbox="allow-top-navigation"></iframe>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<iframe src="https://videos.peertube.example/videos/embed/1" sandbox="allow-popups allow-presentation allow-same-origin allow-scripts" referrerpolicy="strict-origin-when-cross-origin"></iframe>"#
+    );
+}
+
+#[test]
+fn remove_other_embeds() {
+    let mut embed_policy = EmbedPolicy::default();
+    embed_policy
+        .allowed_sources
+        .extend(["*.peertube.example/videos/embed/"]);
+
+    let bubble_bath = BubbleBath {
+        embed_policy: Some(embed_policy),
+        ..BubbleBath::default()
+    };
+
+    for src in [
+        "https://evil.example/embed/abc",
+        "https://www.youtube.com/watch?v=abc",
+        "https://www.youtube.com@evil.example/embed/abc",
+        "https://evil.com&sol;@www.youtube.com/embed/x",
+        "https://evil.com&num;@www.youtube.com/embed/x",
+        "https://evil.com&amp;sol;@www.youtube.com/embed/x",
+        "https://www.youtube.com/embed/../../redirect",
+        "https://peertube.example/videos/embed/1",
+        "javascript:alert(1)",
+    ] {
+        let result = bubble_bath
+            .clean(&format!(r#"<p>a<iframe src="{src}">content</iframe>b</p>"#))
+            .unwrap();
+
+        assert_eq!(result, "<p>ab</p>", "{src}");
+    }
+
+    let result = bubble_bath.clean("<iframe>content</iframe>").unwrap();
+    assert_eq!(result, "");
+}
+
+#[test]
+fn iframes_are_removed_without_policy() {
+    let bubble_bath = BubbleBath {
+        allowed_tags: hashset!["p"],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<p><iframe src="https://www.youtube.com/embed/abc"></iframe></p>"#)
+        .unwrap();
+    assert_eq!(result, "<p></p>");
+}
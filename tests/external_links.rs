@@ -0,0 +1,166 @@
+//! Tests for the handling of external links
+
+use bubble_bath::{hashmap, hashset, BubbleBath, ExternalLinks};
+
+#[test]
+fn internal_links_are_untouched() {
+    let bubble_bath = BubbleBath {
+        external_links: Some(ExternalLinks {
+            internal_hosts: hashset!["example.com"],
+            target_blank: true,
+            ..ExternalLinks::default()
+        }),
+        set_tag_attributes: hashmap![],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(
+            r#"<a href="https://EXAMPLE.com:443/about">about</a><a href="mailto:a@b.c">mail</a>"#,
+        )
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://EXAMPLE.com:443/about">about</a><a href="mailto:a@b.c">mail</a>"#
+    );
+}
+
+#[test]
+fn links_without_host_are_internal() {
+    let bubble_bath = BubbleBath {
+        external_links: Some(ExternalLinks {
+            internal_hosts: hashset!["example.com"],
+            target_blank: true,
+            ..ExternalLinks::default()
+        }),
+        set_tag_attributes: hashmap![],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<a href="mailto:a@b.c">mail</a><a href="tel:+1-555-0100">tel</a>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="mailto:a@b.c">mail</a><a href="tel:+1-555-0100">tel</a>"#
+    );
+}
+
+#[test]
+fn external_links_get_rel_and_target() {
+    let bubble_bath = BubbleBath {
+        external_links: Some(ExternalLinks {
+            internal_hosts: hashset!["example.com"],
+            target_blank: true,
+            ..ExternalLinks::default()
+        }),
+        set_tag_attributes: hashmap![],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://other.example/">other</a>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://other.example/" rel="nofollow ugc noopener noreferrer" target="_blank">other</a>"#
+    );
+}
+
+#[test]
+fn merge_existing_rel() {
+    let mut bubble_bath = BubbleBath {
+        external_links: Some(ExternalLinks {
+            internal_hosts: hashset!["example.com"],
+            ..ExternalLinks::default()
+        }),
+        set_tag_attributes: hashmap![],
+        ..BubbleBath::default()
+    };
+    bubble_bath
+        .allowed_tag_attributes
+        .get_mut("a")
+        .unwrap()
+        .insert("rel");
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://other.example/" rel="me NOFOLLOW">other</a><a href="https://example.com/" rel="me">me</a>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://other.example/" rel="me NOFOLLOW ugc noopener noreferrer">other</a><a href="https://example.com/" rel="me">me</a>"#
+    );
+}
+
+#[test]
+fn ignore_set_tag_attributes() {
+    let mut bubble_bath = BubbleBath {
+        external_links: Some(ExternalLinks {
+            internal_hosts: hashset!["example.com"],
+            ..ExternalLinks::default()
+        }),
+        ..BubbleBath::default()
+    };
+    bubble_bath
+        .allowed_tag_attributes
+        .get_mut("a")
+        .unwrap()
+        .insert("rel");
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://other.example/">other</a><a href="https://example.com/" rel="me">me</a>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://other.example/" rel="nofollow ugc noopener noreferrer">other</a><a href="https://example.com/" rel="me">me</a>"#
+    );
+}
+
+#[test]
+fn hosts_behind_references() {
+    let bubble_bath = BubbleBath {
+        external_links: Some(ExternalLinks {
+            internal_hosts: hashset!["example.com"],
+            ..ExternalLinks::default()
+        }),
+        set_tag_attributes: hashmap![],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://evil.example&sol;@example.com/">x</a>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://evil.example&sol;@example.com/" rel="nofollow ugc noopener noreferrer">x</a>"#
+    );
+}
+
+#[test]
+fn linkified_links() {
+    let bubble_bath = BubbleBath {
+        external_links: Some(ExternalLinks {
+            internal_hosts: hashset!["example.com"],
+            target_blank: true,
+            ..ExternalLinks::default()
+        }),
+        linkify: true,
+        set_tag_attributes: hashmap![],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("see https://example.com/a and https://other.example/b")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"see <a href="https://example.com/a">https:&#47;&#47;example.com&#47;a</a> and <a href="https://other.example/b" rel="nofollow ugc noopener noreferrer" target="_blank">https:&#47;&#47;other.example&#47;b</a>"#
+    );
+}
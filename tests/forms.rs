@@ -0,0 +1,81 @@
+//! Tests for the form policy
+
+use bubble_bath::{hashset, BubbleBath, FormPolicy};
+
+#[test]
+fn keep_disabled_checkboxes() {
+    let bubble_bath = BubbleBath {
+        form_policy: Some(FormPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<ul><li><input type="checkbox" checked onclick="alert(1)" name="x"> done</li><li><input TYPE="CheckBox" autofocus> todo</li></ul>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<ul><li><input type="checkbox" checked disabled=""> done</li><li><input TYPE="CheckBox" disabled=""> todo</li></ul>"#
+    );
+}
+
+#[test]
+fn remove_other_inputs() {
+    let bubble_bath = BubbleBath {
+        form_policy: Some(FormPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<input><input type="text" value="x"><input type="submit"><input type="image" src="https://example.com/a.png"><input type="hidden" name="csrf"><input type=" checkbox"><input type="checkbox&#x20;">"#)
+        .unwrap();
+
+    assert_eq!(result, "");
+}
+
+#[test]
+fn forms_never_submit() {
+    let mut bubble_bath = BubbleBath {
+        form_policy: Some(FormPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    bubble_bath.allowed_tags.extend(["button", "form"]);
+    bubble_bath
+        .allowed_generic_attributes
+        .extend(["autofocus", "form", "formaction"]);
+
+    let result = bubble_bath
+        .clean(r#"<form action="https://evil.example" method="post"><button type="submit" formaction="https://evil.example">Send</button><button form="f" autofocus>Go</button></form>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<button type="button">Send</button><button type="button">Go</button>"#
+    );
+}
+
+#[test]
+fn allow_other_input_types() {
+    let bubble_bath = BubbleBath {
+        form_policy: Some(FormPolicy {
+            allowed_input_types: hashset!["checkbox", "radio"],
+        }),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<input type="radio" checked><input type="text">"#)
+        .unwrap();
+
+    assert_eq!(result, r#"<input type="radio" checked disabled="">"#);
+}
+
+#[test]
+fn default_removes_inputs() {
+    let result = BubbleBath::default()
+        .clean(r#"<form action="https://example.com"><input type="checkbox">text</form>"#)
+        .unwrap();
+
+    assert_eq!(result, "text");
+}
@@ -0,0 +1,43 @@
+//! Tests for the heading level shifting
+
+use bubble_bath::BubbleBath;
+
+#[test]
+fn shift_headings() {
+    let bubble_bath = BubbleBath {
+        heading_offset: 2,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<h1>One</h1><h2>Two</h2><h4>Four</h4><h6>Six</h6>")
+        .unwrap();
+
+    assert_eq!(result, "<h3>One</h3><h4>Two</h4><h6>Four</h6><h6>Six</h6>");
+}
+
+#[test]
+fn normalize_heading_levels() {
+    let bubble_bath = BubbleBath {
+        heading_offset: 1,
+        normalize_heading_levels: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<h3>A</h3><h5>B</h5><h2>C</h2><h1>D</h1><h4>E</h4>")
+        .unwrap();
+
+    assert_eq!(result, "<h2>A</h2><h3>B</h3><h3>C</h3><h2>D</h2><h3>E</h3>");
+}
+
+#[test]
+fn shift_unclosed_headings() {
+    let bubble_bath = BubbleBath {
+        heading_offset: 1,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath.clean("<h1>Title").unwrap();
+    assert_eq!(result, "<h2>Title</h2>");
+}
@@ -0,0 +1,152 @@
+//! Tests for the element hooks
+
+use bubble_bath::{hashmap, BubbleBath, ElementHook};
+
+#[test]
+fn lazy_images() {
+    let lazy_loading: ElementHook<'_> = Box::new(|element| {
+        element.set_attribute("loading", "lazy")?;
+        Ok(())
+    });
+
+    let mut bubble_bath = BubbleBath {
+        element_hooks: hashmap![
+            "img" => vec![lazy_loading],
+        ],
+        ..BubbleBath::default()
+    };
+    bubble_bath
+        .allowed_tag_attributes
+        .get_mut("img")
+        .unwrap()
+        .insert("loading");
+
+    let result = bubble_bath
+        .clean(r#"<img src="https://example.com/a.png" />"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<img src="https://example.com/a.png" loading="lazy" />"#
+    );
+}
+
+#[test]
+fn wrap_tables() {
+    let scroll_container: ElementHook<'_> = Box::new(|element| {
+        element.wrap("div", &[("class", "scroll")]);
+        Ok(())
+    });
+
+    let mut bubble_bath = BubbleBath {
+        element_hooks: hashmap![
+            "table" => vec![scroll_container],
+        ],
+        ..BubbleBath::default()
+    };
+    bubble_bath
+        .allowed_tag_attributes
+        .entry("div")
+        .or_default()
+        .insert("class");
+
+    let result = bubble_bath
+        .clean("<table><tr><td>cell</td></tr></table>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<div class="scroll"><table><tr><td>cell</td></tr></table></div>"#
+    );
+}
+
+#[test]
+fn cant_add_disallowed_attributes() {
+    let evil: ElementHook<'_> = Box::new(|element| {
+        element.set_attribute("onclick", "evil()")?;
+        element.set_attribute("href", "javascript:evil()")?;
+        Ok(())
+    });
+
+    let bubble_bath = BubbleBath {
+        element_hooks: hashmap![
+            "a" => vec![evil],
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath.clean("<a>link</a>").unwrap();
+    assert_eq!(result, r#"<a rel="noopener noreferrer">link</a>"#);
+}
+
+#[test]
+fn cant_insert_markup() {
+    let evil: ElementHook<'_> = Box::new(|element| {
+        element.before("<a onclick=evil()>");
+        element.append("</a>");
+        assert!(!element.wrap("script", &[]));
+        assert!(element.wrap("div", &[("onclick", "evil()"), ("title", "\"><script>")]));
+        Ok(())
+    });
+
+    let bubble_bath = BubbleBath {
+        element_hooks: hashmap![
+            "b" => vec![evil],
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath.clean("<b>text</b>").unwrap();
+    assert_eq!(
+        result,
+        r#"&lt;a onclick=evil()&gt;<div title="&quot;&gt;&lt;script&gt;"><b>text&lt;/a&gt;</b></div>"#
+    );
+}
+
+#[test]
+fn remove_elements() {
+    let unwrap: ElementHook<'_> = Box::new(|element| {
+        element.remove_and_keep_content();
+        Ok(())
+    });
+
+    let bubble_bath = BubbleBath {
+        element_hooks: hashmap![
+            "span" => vec![unwrap],
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath.clean("<p><span>text</p>").unwrap();
+    assert_eq!(result, "<p>text</p>");
+}
+
+#[test]
+fn unwrap_links_by_url() {
+    let unwrap_internal: ElementHook<'_> = Box::new(|element| {
+        let is_internal = element
+            .get_attribute("href")
+            .is_some_and(|href| href.starts_with("https://example.com/"));
+
+        if is_internal {
+            element.remove_and_keep_content();
+        }
+
+        Ok(())
+    });
+
+    let bubble_bath = BubbleBath {
+        element_hooks: hashmap![
+            "a" => vec![unwrap_internal],
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://example.com/a">a</a> <a href="https://example.org/b">b</a>"#)
+        .unwrap();
+    assert_eq!(
+        result,
+        r#"a <a href="https://example.org/b" rel="noopener noreferrer">b</a>"#
+    );
+}
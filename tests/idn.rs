@@ -0,0 +1,154 @@
+//! Tests for the host policy
+#![cfg(feature = "idn")]
+
+use bubble_bath::{BubbleBath, HostPolicy, SuspiciousHosts};
+
+#[test]
+fn convert_to_punycode() {
+    let bubble_bath = BubbleBath {
+        host_policy: Some(HostPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://user@BÜCHER.example:8080/a?b=1&amp;c=2">x</a>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://user@xn--bcher-kva.example:8080/a?b=1&amp;c=2" rel="noopener noreferrer">x</a>"#
+    );
+}
+
+#[test]
+fn reject_suspicious_hosts() {
+    let bubble_bath = BubbleBath {
+        host_policy: Some(HostPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    // All Cyrillic, but confusable with `paypal1.com`
+    let result = bubble_bath
+        .clean(r#"<a href="https://раура1.com">x</a>"#)
+        .unwrap();
+    assert_eq!(result, r#"<a rel="noopener noreferrer">x</a>"#);
+
+    // Latin mixed with Cyrillic
+    let result = bubble_bath
+        .clean(r#"<img src="https://gооgle.com/logo.png">"#)
+        .unwrap();
+    assert_eq!(result, "<img>");
+}
+
+#[test]
+fn reject_suspicious_hosts_behind_references() {
+    let bubble_bath = BubbleBath {
+        host_policy: Some(HostPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    // Browsers decode `&sol;`, so the host is the one before the `@`
+    let result = bubble_bath
+        .clean(r#"<a href="https://раура1.com&sol;@example.com/">x</a>"#)
+        .unwrap();
+
+    assert_eq!(result, r#"<a rel="noopener noreferrer">x</a>"#);
+}
+
+#[test]
+fn keep_unsuspicious_hosts() {
+    let bubble_bath = BubbleBath {
+        host_policy: Some(HostPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://пример.рф/">x</a><a href="https://[::1]/">y</a>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://xn--e1afmkfd.xn--p1ai/" rel="noopener noreferrer">x</a><a href="https://[::1]/" rel="noopener noreferrer">y</a>"#
+    );
+}
+
+#[test]
+fn flag_suspicious_hosts() {
+    let mut bubble_bath = BubbleBath {
+        host_policy: Some(HostPolicy {
+            suspicious_hosts: SuspiciousHosts::Flag {
+                name: "class",
+                value: "suspicious-link",
+            },
+            ..HostPolicy::default()
+        }),
+        ..BubbleBath::default()
+    };
+
+    // The flag isn't allowed on the element yet
+    let input = r#"<a href="https://раура1.com">x</a><img src="https://раура1.com/a.png">"#;
+    let result = bubble_bath.clean(input).unwrap();
+    assert_eq!(
+        result,
+        r#"<a href="https://xn--1-7sba6dbr.com" rel="noopener noreferrer">x</a><img src="https://xn--1-7sba6dbr.com/a.png">"#
+    );
+
+    bubble_bath
+        .allowed_tag_attributes
+        .get_mut("a")
+        .unwrap()
+        .insert("class");
+
+    let result = bubble_bath.clean(input).unwrap();
+    assert_eq!(
+        result,
+        r#"<a href="https://xn--1-7sba6dbr.com" rel="noopener noreferrer" class="suspicious-link">x</a><img src="https://xn--1-7sba6dbr.com/a.png">"#
+    );
+}
+
+#[test]
+fn rewrite_link_text() {
+    let bubble_bath = BubbleBath {
+        host_policy: Some(HostPolicy {
+            rewrite_link_text: true,
+            ..HostPolicy::default()
+        }),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://evil.example/login">https://paypal.com</a>"#)
+        .unwrap();
+    assert_eq!(
+        result,
+        r#"<a href="https://evil.example/login" rel="noopener noreferrer">https:&#47;&#47;evil.example&#47;login</a>"#
+    );
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://evil.example/">paypal.com <b>paypal.com</b></a>"#)
+        .unwrap();
+    assert_eq!(
+        result,
+        r#"<a href="https://evil.example/" rel="noopener noreferrer">https:&#47;&#47;evil.example&#47;<b>paypal.com</b></a>"#
+    );
+}
+
+#[test]
+fn keep_matching_link_text() {
+    let bubble_bath = BubbleBath {
+        host_policy: Some(HostPolicy {
+            rewrite_link_text: true,
+            ..HostPolicy::default()
+        }),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://www.bücher.example/">bücher.example</a> <a href="https://example.com">click here</a>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://www.xn--bcher-kva.example/" rel="noopener noreferrer">bücher.example</a> <a href="https://example.com" rel="noopener noreferrer">click here</a>"#
+    );
+}
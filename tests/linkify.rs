@@ -0,0 +1,90 @@
+//! Tests for the linkification of bare URLs and email addresses
+
+use bubble_bath::BubbleBath;
+
+#[test]
+fn linkify_urls() {
+    let bubble_bath = BubbleBath {
+        linkify: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<p>Check out https://example.com/a?b=1&amp;c=2. (see http://example.com/wiki/Foo_(bar))</p>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<p>Check out <a href="https://example.com/a?b=1&amp;c=2" rel="noopener noreferrer">https:&#47;&#47;example.com&#47;a?b&#61;1&amp;c&#61;2</a>. (see <a href="http://example.com/wiki/Foo_(bar)" rel="noopener noreferrer">http:&#47;&#47;example.com&#47;wiki&#47;Foo_(bar)</a>)</p>"#
+    );
+}
+
+#[test]
+fn linkify_emails() {
+    let bubble_bath = BubbleBath {
+        linkify: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("Mail me at someone@example.com.")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"Mail me at <a href="mailto:someone@example.com" rel="noopener noreferrer">someone@example.com</a>."#
+    );
+
+    // The `mailto:` link has to survive sanitizing it again
+    assert_eq!(bubble_bath.clean(&result).unwrap(), result);
+}
+
+#[test]
+fn check_url_schemes() {
+    let bubble_bath = BubbleBath {
+        linkify: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("javascript://%0aalert(1) and gopher://example.com")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "javascript:&#47;&#47;%0aalert(1) and gopher:&#47;&#47;example.com"
+    );
+}
+
+#[test]
+fn skip_links_and_code() {
+    let bubble_bath = BubbleBath {
+        linkify: true,
+        ..BubbleBath::default()
+    };
+
+    let input = r#"<a href="https://example.com">https://example.com</a><code>https://example.com</code><pre><b>https://example.com</b></pre>"#;
+    let result = bubble_bath.clean(input).unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://example.com" rel="noopener noreferrer">https:&#47;&#47;example.com</a><code>https:&#47;&#47;example.com</code><pre><b>https:&#47;&#47;example.com</b></pre>"#
+    );
+}
+
+#[test]
+fn escape_links() {
+    let bubble_bath = BubbleBath {
+        linkify: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"https://example.com/"onmouseover="alert(1)&lt;script&gt;"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://example.com/" rel="noopener noreferrer">https:&#47;&#47;example.com&#47;</a>&quot;onmouseover&#61;&quot;alert(1)&lt;script&gt;"#
+    );
+}
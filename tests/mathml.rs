@@ -0,0 +1,77 @@
+//! Tests for the `MathML` preset and the namespace tracking
+
+use bubble_bath::BubbleBath;
+
+#[test]
+fn keep_formula() {
+    let bubble_bath = BubbleBath::default().union(&BubbleBath::mathml());
+
+    let input = r#"<p>Solve <math display="block"><mrow><mi>x</mi><mo>+</mo><mfrac linethickness="2"><mn>1</mn><msqrt><mn>2</mn></msqrt></mfrac></mrow></math></p>"#;
+    let result = bubble_bath.clean(input).unwrap();
+
+    assert_eq!(result, input);
+}
+
+#[test]
+fn remove_disallowed_attributes() {
+    let bubble_bath = BubbleBath::default().union(&BubbleBath::mathml());
+
+    let result = bubble_bath
+        .clean(r#"<math onclick="alert(1)"><mi href="javascript:alert(1)" mathvariant="bold">x</mi></math>"#)
+        .unwrap();
+
+    assert_eq!(result, r#"<math><mi mathvariant="bold">x</mi></math>"#);
+}
+
+#[test]
+fn remove_annotation_xml() {
+    let bubble_bath = BubbleBath::default().union(&BubbleBath::mathml());
+
+    let result = bubble_bath
+        .clean(r#"<math><semantics><mi>x</mi><annotation-xml encoding="text/html"><img src="x" onerror="alert(1)"></annotation-xml><annotation encoding="TeX">x</annotation></semantics></math>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<math><semantics><mi>x</mi><annotation encoding="TeX">x</annotation></semantics></math>"#
+    );
+}
+
+#[test]
+fn remove_html_outside_of_integration_points() {
+    let bubble_bath = BubbleBath::default().union(&BubbleBath::mathml());
+
+    let result = bubble_bath
+        .clean("<math><mrow><b>bold</b><mi><b>x</b></mi></mrow></math>")
+        .unwrap();
+
+    assert_eq!(result, "<math><mrow><mi><b>x</b></mi></mrow></math>");
+}
+
+#[test]
+fn neutralize_mutation_xss() {
+    let bubble_bath = BubbleBath::default().union(&BubbleBath::mathml());
+
+    let result = bubble_bath
+        .clean(r"<math><mtext><table><mglyph><style><img src=x onerror=alert(1)></style></mglyph></table></mtext></math>")
+        .unwrap();
+
+    assert!(!result.contains("<img"), "{result}");
+    assert!(!result.contains("<mglyph"), "{result}");
+
+    let result = bubble_bath
+        .clean(r#"<math><mi><mglyph><svg><mtext><textarea><a title="</textarea><img src=x onerror=alert(1)>">"#)
+        .unwrap();
+
+    assert!(!result.contains("<img"), "{result}");
+    assert!(!result.contains("<mglyph"), "{result}");
+}
+
+#[test]
+fn default_removes_mathml() {
+    let result = BubbleBath::default()
+        .clean("<math><mi>x</mi><annotation-xml><b>y</b></annotation-xml></math>")
+        .unwrap();
+
+    assert_eq!(result, "x<b>y</b>");
+}
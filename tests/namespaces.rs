@@ -0,0 +1,152 @@
+//! Tests for the namespace-aware handling of foreign content
+
+use bubble_bath::{hashmap, hashset, BubbleBath, CssSanitizer, Namespace, SvgPolicy};
+
+#[test]
+fn allow_lists_are_keyed_by_namespace() {
+    let bubble_bath = BubbleBath {
+        allowed_foreign_tags: hashmap![
+            Namespace::MathMl => hashset!["math", "mi"],
+            Namespace::Svg => hashset!["circle", "svg"],
+        ],
+        allowed_foreign_tag_attributes: hashmap![
+            Namespace::Svg => hashmap![
+                "circle" => hashset!["r"],
+            ],
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<svg><circle r="1" /><b>bold</b><mi>x</mi></svg><b>bold</b><math><circle r="1">x</circle><mi>y</mi></math>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<svg><circle r="1" /></svg><b>bold</b><math><mi>y</mi></math>"#
+    );
+}
+
+#[test]
+fn attribute_allow_lists_are_keyed_by_namespace() {
+    let mut bubble_bath = BubbleBath {
+        allowed_foreign_tags: hashmap![
+            Namespace::Svg => hashset!["svg", "title"],
+        ],
+        allowed_foreign_tag_attributes: hashmap![
+            Namespace::Svg => hashmap![
+                "title" => hashset!["class"],
+            ],
+        ],
+        ..BubbleBath::default()
+    };
+    bubble_bath.allowed_tags.insert("title");
+    bubble_bath
+        .allowed_tag_attributes
+        .insert("title", hashset!["dir"]);
+
+    let result = bubble_bath
+        .clean(r#"<svg><title class="icon" dir="rtl">svg</title></svg><title class="icon" dir="rtl">html</title>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<svg><title class="icon">svg</title></svg><title dir="rtl">html</title>"#
+    );
+}
+
+#[test]
+fn unwrap_disallowed_roots() {
+    let bubble_bath = BubbleBath::default();
+
+    let result = bubble_bath
+        .clean("<svg><b>bold</b></svg><math><i>italic</i></math>")
+        .unwrap();
+    assert_eq!(result, "<b>bold</b><i>italic</i>");
+}
+
+#[test]
+fn keep_html_in_integration_points() {
+    let bubble_bath = BubbleBath {
+        allowed_foreign_tags: hashmap![
+            Namespace::MathMl => hashset!["math", "mi"],
+            Namespace::Svg => hashset!["svg", "title"],
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<svg><title><b>bold</b></title></svg><math><mi><b>bold</b></mi></math>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "<svg><title><b>bold</b></title></svg><math><mi><b>bold</b></mi></math>"
+    );
+}
+
+#[test]
+fn remove_raw_text_elements_in_foreign_content() {
+    let mut bubble_bath = BubbleBath {
+        allowed_foreign_tags: hashmap![
+            Namespace::MathMl => hashset!["math", "title"],
+            Namespace::Svg => hashset!["noscript", "style", "svg"],
+        ],
+        remove_content_tags: hashset![],
+        ..BubbleBath::default()
+    };
+    bubble_bath.allowed_tags.extend(["noscript", "title"]);
+
+    let result = bubble_bath
+        .clean(r#"<svg><style><img src="x" onerror="alert(1)"></style><noscript><b>bold</b></noscript></svg>"#)
+        .unwrap();
+    assert_eq!(result, "<svg></svg>");
+
+    let result = bubble_bath
+        .clean(r#"<svg><noscript><p title="</noscript><img src=x onerror=alert(1)>"></noscript></svg>"#)
+        .unwrap();
+    assert!(!result.contains("<img"), "{result}");
+
+    let result = bubble_bath
+        .clean("<math><title><b>bold</b></title></math>")
+        .unwrap();
+    assert_eq!(result, "<math></math>");
+
+    let result = bubble_bath.clean("<title><b>bold</b></title>").unwrap();
+    assert_eq!(result, "<title>&lt;b&gt;bold&lt;&#47;b&gt;</title>");
+}
+
+#[test]
+fn only_sanitize_html_style_tags() {
+    let bubble_bath = BubbleBath {
+        allowed_foreign_tags: hashmap![
+            Namespace::Svg => hashset!["style", "svg"],
+        ],
+        css_sanitizer: Some(CssSanitizer::default()),
+        sanitize_style_tags: true,
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<style>p { color: red }</style><svg><style><img src=x onerror=alert(1)>p { color: red }</style></svg>")
+        .unwrap();
+
+    assert_eq!(result, "<style>p { color: red; }\n</style><svg></svg>");
+}
+
+#[test]
+fn svg_policy_never_allows_raw_text_elements() {
+    let mut svg_policy = SvgPolicy::default();
+    svg_policy.allowed_tags.insert("style");
+
+    let bubble_bath = BubbleBath {
+        svg_policy: Some(svg_policy),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<svg><style>circle { fill: red }</style></svg>")
+        .unwrap();
+
+    assert_eq!(result, "<svg></svg>");
+}
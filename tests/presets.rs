@@ -0,0 +1,63 @@
+//! Snapshot tests of the presets against the inputs in `tests/inputs`
+
+use bubble_bath::BubbleBath;
+use insta::assert_snapshot;
+use std::fs;
+
+fn snapshot(name: &str, bubble_bath: &BubbleBath<'_>) {
+    insta::glob!("inputs/*", |path| {
+        let input = fs::read_to_string(path).unwrap();
+        assert_snapshot!(name, bubble_bath.clean(&input).unwrap());
+    });
+}
+
+#[test]
+fn text_only() {
+    snapshot("text_only", &BubbleBath::text_only());
+}
+
+#[test]
+fn basic_formatting() {
+    snapshot("basic_formatting", &BubbleBath::basic_formatting());
+}
+
+#[test]
+fn relaxed() {
+    snapshot("relaxed", &BubbleBath::relaxed());
+}
+
+#[test]
+fn email() {
+    snapshot("email", &BubbleBath::email());
+}
+
+#[test]
+fn activitypub() {
+    snapshot("activitypub", &BubbleBath::activitypub());
+}
+
+#[test]
+fn relaxed_srcset() {
+    let bubble_bath = BubbleBath::relaxed();
+
+    let result = bubble_bath
+        .clean(r#"<picture><source srcset="https://example.com/a.png 1x, https://example.com/b,c.png 2x" /></picture>"#)
+        .unwrap();
+    assert_eq!(
+        result,
+        r#"<picture><source srcset="https://example.com/a.png 1x, https://example.com/b,c.png 2x" /></picture>"#
+    );
+
+    for srcset in [
+        "https://example.com/a.png 1x, data:image/svg+xml,evil 2x",
+        "https://example.com/a.png 1x, /relative.png 2x",
+        "https://example.com/a.png&NewLine;1x,javascript:alert(1)",
+    ] {
+        let result = bubble_bath
+            .clean(&format!(
+                r#"<picture><source srcset="{srcset}" /></picture>"#
+            ))
+            .unwrap();
+        assert_eq!(result, "<picture><source/></picture>");
+    }
+}
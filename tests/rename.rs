@@ -0,0 +1,60 @@
+//! Tests for the tag renaming
+
+use bubble_bath::{hashmap, BubbleBath};
+
+#[test]
+fn rename_tags() {
+    let bubble_bath = BubbleBath {
+        rename_tags: hashmap![
+            "b" => "strong",
+            "center" => "div",
+            "font" => "span",
+            "h1" => "h3",
+            "i" => "em",
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<h1 title="a &amp; &quot;b&quot;">Title</h1><p><b>bold</b> <i>italic</i> <font size="20">font</font></p><center>centered</center>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<h3 title="a &amp; &quot;b&quot;">Title</h3><p><strong>bold</strong> <em>italic</em> <span>font</span></p><div>centered</div>"#
+    );
+}
+
+#[test]
+fn rename_implicitly_closed_tags() {
+    let bubble_bath = BubbleBath {
+        rename_tags: hashmap![
+            "b" => "strong",
+            "i" => "em",
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath.clean("<div><b><i>bold</div>after").unwrap();
+    assert_eq!(result, "<div><strong><em>bold</em></strong></div>after");
+
+    let result = bubble_bath.clean("<b>unclosed").unwrap();
+    assert_eq!(result, "<strong>unclosed</strong>");
+}
+
+#[test]
+fn allow_lists_apply_to_new_name() {
+    let bubble_bath = BubbleBath {
+        rename_tags: hashmap![
+            "a" => "span",
+            "script" => "p",
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://example.com">link</a><script>evil()</script>"#)
+        .unwrap();
+
+    assert_eq!(result, "<span>link</span>");
+}
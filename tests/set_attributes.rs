@@ -0,0 +1,79 @@
+//! Tests for setting attributes on tags
+
+use bubble_bath::{hashmap, hashset, BubbleBath};
+
+#[test]
+fn default_attributes() {
+    let mut bubble_bath = BubbleBath {
+        default_tag_attributes: hashmap![
+            "img" => hashmap![
+                "loading" => "lazy",
+            ],
+            "p" => hashmap![
+                "dir" => "auto",
+            ],
+        ],
+        ..BubbleBath::default()
+    };
+    bubble_bath
+        .allowed_tag_attributes
+        .insert("p", hashset!["dir"]);
+
+    let result = bubble_bath
+        .clean(r#"<p dir="rtl">a</p><p>b</p><img src="https://example.com/a.png">"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<p dir="rtl">a</p><p dir="auto">b</p><img src="https://example.com/a.png" loading="lazy">"#
+    );
+}
+
+#[test]
+fn merge_attributes() {
+    let bubble_bath = BubbleBath {
+        allowed_generic_attributes: hashset!["class"],
+        merge_tag_attributes: hashmap![
+            "p" => hashmap![
+                "class" => "user-content  note",
+            ],
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<p class="note big">a</p><p>b</p>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<p class="note big user-content">a</p><p class="user-content note">b</p>"#
+    );
+}
+
+#[test]
+fn modes_are_applied_in_order() {
+    let bubble_bath = BubbleBath {
+        default_tag_attributes: hashmap![
+            "a" => hashmap![
+                "rel" => "nofollow",
+            ],
+        ],
+        linkify: true,
+        merge_tag_attributes: hashmap![
+            "a" => hashmap![
+                "rel" => "ugc noopener",
+            ],
+        ],
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<a href="https://example.com">a</a> https://example.org"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://example.com" rel="noopener noreferrer ugc">a</a> <a href="https://example.org" rel="noopener noreferrer ugc">https:&#47;&#47;example.org</a>"#
+    );
+}
@@ -0,0 +1,95 @@
+//! Tests for the SVG policy
+
+use bubble_bath::{BubbleBath, SvgPolicy};
+
+#[test]
+fn keep_safe_svg() {
+    let bubble_bath = BubbleBath {
+        svg_policy: Some(SvgPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    let input = r#"<svg viewBox="0 0 10 10" xmlns="http://www.w3.org/2000/svg"><title>Diagram</title><defs><linearGradient id="g"><stop offset="0" stop-color="red" /></linearGradient></defs><path d="M0 0L10 10" fill="url(#g)" stroke-width="2" /><text x="1">Hi</text></svg>"#;
+    let result = bubble_bath.clean(input).unwrap();
+
+    assert_eq!(result, input);
+}
+
+#[test]
+fn remove_dangerous_content() {
+    let bubble_bath = BubbleBath {
+        svg_policy: Some(SvgPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<svg onload="alert(1)"><script>alert(1)</script><foreignObject>hi<a href="https://example.com">x</a></foreignObject><circle r="1" onclick="alert(1)" /></svg>"#)
+        .unwrap();
+
+    assert_eq!(result, r#"<svg><circle r="1" /></svg>"#);
+
+    let result = bubble_bath.clean("<svg/onload=alert('XSS')>").unwrap();
+    assert_eq!(result, "<svg></svg>");
+}
+
+#[test]
+fn clean_references() {
+    let bubble_bath = BubbleBath {
+        svg_policy: Some(SvgPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r##"<svg><use href="#a" /><use xlink:href="javascript:alert(1)" /><use href="https://evil.example/a.svg#a" /><rect fill="url(https://evil.example/a)" mask="url( '#m')" /><rect fill="\75 rl(https://evil.example/t)" stroke="\000075rl(https://evil.example/t)" /></svg>"##)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r##"<svg><use href="#a" /><use/><use/><rect mask="url( '#m')" /><rect/></svg>"##
+    );
+}
+
+#[test]
+fn remove_animations_setting_references() {
+    let bubble_bath = BubbleBath {
+        svg_policy: Some(SvgPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r##"<svg><a href="#x"><animate attributeName="href" to="javascript:alert(1)" /></a><set attributeName="XLINK:HREF" to="javascript:alert(1)" /><animate attributeName="fill" to="url(javascript:alert(1))" /><animate attributeName="opacity" from="0" to="1" dur="1s" /></svg>"##)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<svg><animate attributeName="fill" /><animate attributeName="opacity" from="0" to="1" dur="1s" /></svg>"#
+    );
+}
+
+#[test]
+fn prefix_ids() {
+    let bubble_bath = BubbleBath {
+        id_prefix: Some("user-"),
+        svg_policy: Some(SvgPolicy::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r##"<svg><circle id="c" r="1" clip-path="url(#clip)" /><use href="#c" /></svg>"##)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r##"<svg><circle id="user-c" r="1" clip-path="url(#user-clip)" /><use href="#user-c" /></svg>"##
+    );
+    assert_eq!(bubble_bath.clean(&result).unwrap(), result);
+}
+
+#[test]
+fn without_policy() {
+    let result = BubbleBath::default()
+        .clean(r#"<svg><circle r="1" /></svg>"#)
+        .unwrap();
+
+    assert_eq!(result, "");
+}
@@ -0,0 +1,146 @@
+//! Tests for the table structure repair
+
+use bubble_bath::{BubbleBath, TableRepair};
+
+#[test]
+fn keep_valid_tables() {
+    let bubble_bath = BubbleBath {
+        table_repair: Some(TableRepair::default()),
+        ..BubbleBath::default()
+    };
+
+    let input =
+        "<table><thead><tr><th>h</th></tr></thead><tbody><tr><td>a</td></tr></tbody></table>";
+    assert_eq!(bubble_bath.clean(input).unwrap(), input);
+}
+
+#[test]
+fn insert_missing_wrappers() {
+    let bubble_bath = BubbleBath {
+        table_repair: Some(TableRepair::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<table><td>a</td></table><table><thead><tr><th>h</th></tr></thead><tr><td>b</td></tr><tr><td>c</td></tr></table>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "<table><tbody><tr><td>a</td></tr></tbody></table><table><thead><tr><th>h</th></tr></thead><tbody><tr><td>b</td></tr><tr><td>c</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn close_unclosed_cells() {
+    let bubble_bath = BubbleBath {
+        table_repair: Some(TableRepair::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<table><caption>c<tr><td>a<b>bold<td>b<tr><td>c</td></tr></table>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "<table><caption>c</caption><tbody><tr><td>a<b>bold</b></td><td>b</td></tr><tr><td>c</td></tr></tbody></table>"
+    );
+
+    let result = bubble_bath
+        .clean("<table><td>a<td>b</table><div><table><td><b>c</div>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "<table><tbody><tr><td>a</td><td>b</td></tr></tbody></table><div><table><tbody><tr><td><b>c</b></td></tr></tbody></table></div>"
+    );
+}
+
+#[test]
+fn keep_nested_tables() {
+    let bubble_bath = BubbleBath {
+        table_repair: Some(TableRepair::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<table><tr><td><table><td>inner</td></table>outer</td></tr></table>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "<table><tbody><tr><td><table><tbody><tr><td>inner</td></tr></tbody></table>outer</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn remove_orphaned_elements() {
+    let bubble_bath = BubbleBath {
+        table_repair: Some(TableRepair::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<td>cell</td> <tr><th>header</th></tr> <caption>caption</caption>")
+        .unwrap();
+
+    assert_eq!(result, "cell header caption");
+}
+
+#[test]
+fn clamp_spans() {
+    let bubble_bath = BubbleBath {
+        table_repair: Some(TableRepair {
+            max_colspan: 10,
+            max_rowspan: 5,
+        }),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<table><tr><td colspan="9999" rowspan="abc">a</td><td colspan="0" rowspan=" 3px">b</td><td rowspan="0">c</td></tr></table>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<table><tbody><tr><td colspan="10">a</td><td colspan="1" rowspan="3">b</td><td rowspan="0">c</td></tr></tbody></table>"#
+    );
+}
+
+#[test]
+fn raise_maximum_spans_to_minimum() {
+    let bubble_bath = BubbleBath {
+        table_repair: Some(TableRepair {
+            max_colspan: 0,
+            max_rowspan: 0,
+        }),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(r#"<table><tr><td colspan="3" rowspan="2">a</td></tr></table>"#)
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<table><tbody><tr><td colspan="1" rowspan="0">a</td></tr></tbody></table>"#
+    );
+}
+
+#[test]
+fn repaired_tables_are_idempotent() {
+    let bubble_bath = BubbleBath {
+        table_repair: Some(TableRepair::default()),
+        ..BubbleBath::default()
+    };
+
+    for input in [
+        "<table><tr><td>a<td>b</table>",
+        "<table><tr><td>a<td>b",
+        "<div><table><td><b>c</div>",
+        "<table><td>a</td><caption>c</caption><col><tr><td>b</td></table>",
+    ] {
+        assert!(bubble_bath.is_idempotent(input).unwrap());
+    }
+}
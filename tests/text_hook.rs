@@ -0,0 +1,64 @@
+//! Tests for the text node hook
+
+use bubble_bath::BubbleBath;
+
+#[test]
+fn transform_text() {
+    let bubble_bath = BubbleBath {
+        text_hook: Some(Box::new(|text, _elements| text.to_uppercase())),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<p>hello <b>world</b> &amp; &lt;everyone&gt;</p>")
+        .unwrap();
+
+    assert_eq!(result, "<p>HELLO <b>WORLD</b> &amp; &lt;EVERYONE&gt;</p>");
+}
+
+#[test]
+fn escape_output() {
+    let bubble_bath = BubbleBath {
+        text_hook: Some(Box::new(|text, _elements| {
+            format!("<script>{text}</script>")
+        })),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath.clean("<p>hi</p>").unwrap();
+
+    assert_eq!(result, "<p>&lt;script&gt;hi&lt;&#47;script&gt;</p>");
+}
+
+#[test]
+fn enclosing_elements() {
+    let bubble_bath = BubbleBath {
+        text_hook: Some(Box::new(|text, elements| {
+            format!("{text}[{}]", elements.join("/"))
+        })),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<div><p>a<b>b</b><font>c</font></p></div>d<script>e</script>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "<div><p>a[div&#47;p]<b>b[div&#47;p&#47;b]</b>c[div&#47;p]</p></div>d[]"
+    );
+}
+
+#[test]
+fn decode_html5_references() {
+    let bubble_bath = BubbleBath {
+        text_hook: Some(Box::new(|text, _elements| format!("[{text}]"))),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<p>a&sol;b&commat;c&num;&Tab;&notin; &not &ampx;</p>")
+        .unwrap();
+
+    assert_eq!(result, "<p>[a&#47;b@c#\t∉ ¬ &amp;x;]</p>");
+}
@@ -0,0 +1,108 @@
+//! Tests for turning mentions and hashtags into links
+
+use bubble_bath::{BubbleBath, Token, TokenLinks};
+
+#[test]
+fn link_tokens() {
+    let bubble_bath = BubbleBath {
+        token_links: Some(TokenLinks::new(
+            "https://example.com/tags/{name}",
+            "https://example.com/@{acct}",
+        )),
+        ..BubbleBath::default()
+    };
+
+    let (result, tokens) = bubble_bath
+        .clean_with_tokens("<p>Hi @alice@example.org and @bob! #Rust #rust #1 #Über</p>")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<p>Hi <a href="https://example.com/@alice%40example.org" rel="noopener noreferrer">@alice@example.org</a> and <a href="https://example.com/@bob" rel="noopener noreferrer">@bob</a>! <a href="https://example.com/tags/Rust" rel="noopener noreferrer">#Rust</a> <a href="https://example.com/tags/rust" rel="noopener noreferrer">#rust</a> #1 <a href="https://example.com/tags/%C3%9Cber" rel="noopener noreferrer">#Über</a></p>"#
+    );
+    assert_eq!(
+        tokens,
+        [
+            Token::Mention {
+                username: "alice".into(),
+                host: Some("example.org".into()),
+            },
+            Token::Mention {
+                username: "bob".into(),
+                host: None,
+            },
+            Token::Hashtag {
+                name: "Rust".into()
+            },
+            Token::Hashtag {
+                name: "rust".into()
+            },
+            Token::Hashtag {
+                name: "Über".into()
+            },
+        ]
+    );
+}
+
+#[test]
+fn ignore_emails_and_links() {
+    let bubble_bath = BubbleBath {
+        token_links: Some(TokenLinks::new(
+            "https://example.com/tags/{name}",
+            "https://example.com/@{acct}",
+        )),
+        ..BubbleBath::default()
+    };
+
+    let (result, tokens) = bubble_bath
+        .clean_with_tokens(
+            r#"mail@example.com <a href="https://example.com">#tag</a> <code>@user</code>"#,
+        )
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"mail@example.com <a href="https://example.com" rel="noopener noreferrer">#tag</a> <code>@user</code>"#
+    );
+    assert!(tokens.is_empty());
+}
+
+#[test]
+fn custom_recognizer() {
+    let mut token_links = TokenLinks::new(
+        "https://example.com/tags/{name}",
+        "https://example.com/@{acct}",
+    );
+    token_links.recognizer = Box::new(|text| {
+        let start = text.find('$')?;
+        let len = text[start + 1..]
+            .find(|chr: char| !chr.is_ascii_uppercase())
+            .unwrap_or(text.len() - start - 1);
+
+        let token = Token::Hashtag {
+            name: text[start + 1..start + 1 + len].to_string(),
+        };
+        Some((start..start + 1 + len, token))
+    });
+
+    let bubble_bath = BubbleBath {
+        linkify: true,
+        token_links: Some(token_links),
+        ..BubbleBath::default()
+    };
+
+    let (result, tokens) = bubble_bath
+        .clean_with_tokens("$RUST on https://example.com/$X")
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://example.com/tags/RUST" rel="noopener noreferrer">$RUST</a> on <a href="https://example.com/$X" rel="noopener noreferrer">https:&#47;&#47;example.com&#47;$X</a>"#
+    );
+    assert_eq!(
+        tokens,
+        [Token::Hashtag {
+            name: "RUST".into()
+        }]
+    );
+}
@@ -1,3 +1,5 @@
+//! Snapshot tests against the inputs in `tests/inputs`
+
 use bubble_bath::BubbleBath;
 use insta::assert_snapshot;
 use std::fs;
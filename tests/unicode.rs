@@ -0,0 +1,81 @@
+//! Tests for the Unicode filter
+
+use bubble_bath::{BubbleBath, UnicodeFilter};
+
+#[test]
+fn remove_bidi_controls() {
+    let bubble_bath = BubbleBath {
+        unicode_filter: Some(UnicodeFilter::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<p>access\u{202E}&#x2066;level\u{2069} is \u{200F}admin</p>")
+        .unwrap();
+
+    assert_eq!(result, "<p>accesslevel is \u{200F}admin</p>");
+}
+
+#[test]
+fn remove_invisible_and_control_characters() {
+    let bubble_bath = BubbleBath {
+        unicode_filter: Some(UnicodeFilter::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<p>pay\u{200B}pal\u{7}&#x1b;\u{FEFF} 👩\u{200D}💻 a\u{200D}b\tc</p>")
+        .unwrap();
+
+    assert_eq!(result, "<p>paypal 👩\u{200D}💻 ab\tc</p>");
+}
+
+#[test]
+fn filter_attribute_values() {
+    let bubble_bath = BubbleBath {
+        unicode_filter: Some(UnicodeFilter::default()),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean(
+            "<a href=\"https://example.com/\u{202E}fdp.exe\" title=\"a&amp;b&#x200B;&quot;\">x</a>",
+        )
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"<a href="https://example.com/fdp.exe" title="a&amp;b&quot;" rel="noopener noreferrer">x</a>"#
+    );
+}
+
+#[test]
+fn configurable() {
+    let bubble_bath = BubbleBath {
+        unicode_filter: Some(UnicodeFilter {
+            remove_bidi_controls: false,
+            ..UnicodeFilter::default()
+        }),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath.clean("a\u{202E}b\u{200B}c").unwrap();
+    assert_eq!(result, "a\u{202E}bc");
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[test]
+fn normalize_nfc() {
+    let bubble_bath = BubbleBath {
+        unicode_filter: Some(UnicodeFilter {
+            normalize_nfc: true,
+            ..UnicodeFilter::default()
+        }),
+        ..BubbleBath::default()
+    };
+
+    let result = bubble_bath
+        .clean("<p title=\"Cafe\u{301}\">Cafe\u{301}</p>")
+        .unwrap();
+    assert_eq!(result, "<p title=\"Caf\u{e9}\">Caf\u{e9}</p>");
+}
@@ -0,0 +1,153 @@
+//! Tests for the verification of the output against mutation XSS
+
+use bubble_bath::{
+    hashmap, BubbleBath, ContentModel, ElementHook, Error, TableRepair, TokenLinks, Verification,
+};
+use std::fs;
+
+fn assert_idempotent(bubble_bath: &BubbleBath<'_>) {
+    insta::glob!("inputs/*", |path| {
+        let input = fs::read_to_string(path).unwrap();
+        assert!(bubble_bath.is_idempotent(&input).unwrap());
+    });
+}
+
+#[test]
+fn presets_are_idempotent() {
+    assert_idempotent(&BubbleBath::default());
+    assert_idempotent(&BubbleBath {
+        preserve_escaped: true,
+        ..BubbleBath::default()
+    });
+    assert_idempotent(&BubbleBath::text_only());
+    assert_idempotent(&BubbleBath::basic_formatting());
+    assert_idempotent(&BubbleBath::relaxed());
+    assert_idempotent(&BubbleBath::email());
+    assert_idempotent(&BubbleBath::activitypub());
+}
+
+#[test]
+fn cleanup_options_are_idempotent() {
+    for content_model in [ContentModel::Move, ContentModel::Unwrap] {
+        assert_idempotent(&BubbleBath {
+            collapse_whitespace: true,
+            content_model: Some(content_model),
+            max_line_breaks: Some(1),
+            remove_empty_tags: true,
+            table_repair: Some(TableRepair::default()),
+            ..BubbleBath::relaxed()
+        });
+    }
+}
+
+#[test]
+fn detect_mutation() {
+    let mut bubble_bath = BubbleBath::default();
+    bubble_bath.allowed_tags.insert("noscript");
+
+    let input = r#"<noscript><p title="</noscript><img src=x onerror=alert(1)>"></noscript>"#;
+    assert!(!bubble_bath.is_idempotent(input).unwrap());
+    assert!(bubble_bath.clean(input).is_ok());
+
+    let bubble_bath = BubbleBath {
+        verification: Some(Verification::Reject),
+        ..bubble_bath
+    };
+    assert!(matches!(bubble_bath.clean(input), Err(Error::Mutation)));
+    assert_eq!(bubble_bath.clean("<p>safe</p>").unwrap(), "<p>safe</p>");
+}
+
+#[test]
+fn escape_on_mutation() {
+    let mut bubble_bath = BubbleBath {
+        verification: Some(Verification::Escape),
+        ..BubbleBath::default()
+    };
+    bubble_bath.allowed_tags.insert("noscript");
+
+    let result = bubble_bath
+        .clean("<noscript><b>bold</b></noscript>")
+        .unwrap();
+    assert_eq!(
+        result,
+        "&lt;noscript&gt;&lt;b&gt;bold&lt;&#47;b&gt;&lt;&#47;noscript&gt;"
+    );
+}
+
+#[test]
+fn transforms_are_not_applied_twice() {
+    let wrap_tables: ElementHook<'_> = Box::new(|element| {
+        element.wrap("div", &[]);
+        Ok(())
+    });
+
+    for (mut bubble_bath, input, expected) in [
+        (
+            BubbleBath {
+                heading_offset: 1,
+                ..BubbleBath::default()
+            },
+            "<h1>Title</h1>",
+            "<h2>Title</h2>",
+        ),
+        (
+            BubbleBath {
+                normalize_heading_levels: true,
+                heading_offset: 1,
+                ..BubbleBath::default()
+            },
+            "<h1>Title</h1><h4>Section</h4>",
+            "<h2>Title</h2><h3>Section</h3>",
+        ),
+        (
+            BubbleBath {
+                linkify: true,
+                ..BubbleBath::default()
+            },
+            "see https://example.com and a@b.c",
+            r#"see <a href="https://example.com" rel="noopener noreferrer">https:&#47;&#47;example.com</a> and <a href="mailto:a@b.c" rel="noopener noreferrer">a@b.c</a>"#,
+        ),
+        (
+            BubbleBath {
+                token_links: Some(TokenLinks::new(
+                    "https://example.com/tags/{name}",
+                    "https://example.com/@{acct}",
+                )),
+                ..BubbleBath::default()
+            },
+            "hi @bob #rust",
+            r#"hi <a href="https://example.com/@bob" rel="noopener noreferrer">@bob</a> <a href="https://example.com/tags/rust" rel="noopener noreferrer">#rust</a>"#,
+        ),
+        (
+            BubbleBath {
+                id_prefix: Some("user-"),
+                ..BubbleBath::default()
+            },
+            r##"<a href="#x">x</a>"##,
+            r##"<a href="#user-x" rel="noopener noreferrer">x</a>"##,
+        ),
+        (
+            BubbleBath {
+                text_hook: Some(Box::new(|text, _elements| format!("[{text}]"))),
+                ..BubbleBath::default()
+            },
+            "<p>a</p>",
+            "<p>[a]</p>",
+        ),
+        (
+            BubbleBath {
+                element_hooks: hashmap!["table" => vec![wrap_tables]],
+                ..BubbleBath::default()
+            },
+            "<table></table>",
+            "<div><table></table></div>",
+        ),
+    ] {
+        assert!(bubble_bath.is_idempotent(input).unwrap(), "{input}");
+
+        for verification in [Verification::Escape, Verification::Reject] {
+            bubble_bath.verification = Some(verification);
+            assert_eq!(bubble_bath.clean(input).unwrap(), expected);
+        }
+    }
+}